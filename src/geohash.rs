@@ -1,5 +1,8 @@
+use crate::{Coord, ZpathError};
+use std::collections::HashSet;
+
 // Geohashes are represented using characters from a Base32 alphabet variant called the 'geohash alphabet' or '32ghs'
-const BASE_32GHS: &'static [u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const BASE_32GHS: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
 
 // The following are lookup tables used in get_adjacent_cell(); they vary depending on direction and type of geohash
 const BORDERS_A: [char; 4] = ['p', 'r', 'x', 'z'];
@@ -24,7 +27,8 @@ const NEIGHBORS_D: [char; 32] = [
     't', 'q', 'r', 'w', 'x', 'u', 'v', 'h', 'j', 'y', 'z', 'n', 'p',
 ];
 
-#[derive(Clone, Copy, PartialEq)]
+/// The four cardinal directions in which an adjacent geohash cell can be found.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction {
     North,
     East,
@@ -32,6 +36,95 @@ pub enum Direction {
     West,
 }
 
+/// Represents a cell of the Earth as it's narrowed down by repeatedly halving it, first
+/// along longitude and then along latitude, alternating between the two. Each halving
+/// gives one bit of a geohash or Morton code: `1` for the upper half, `0` for the lower.
+struct Bisection {
+    lat_min: Coord,
+    lat_max: Coord,
+    lon_min: Coord,
+    lon_max: Coord,
+    longitude_bit: bool,
+}
+
+impl Bisection {
+    /// Starts from the entire range of Earth.
+    fn new() -> Self {
+        Bisection {
+            lat_min: -90.0,
+            lat_max: 90.0,
+            lon_min: -180.0,
+            lon_max: 180.0,
+            longitude_bit: true,
+        }
+    }
+
+    /// Halves the cell, keeping the upper half if `is_set` and the lower half otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_set` - Whether the next bit is a `1`.
+    fn narrow(&mut self, is_set: bool) {
+        if self.longitude_bit {
+            let midpoint = (self.lon_min + self.lon_max) / 2.0;
+            if is_set {
+                self.lon_min = midpoint;
+            } else {
+                self.lon_max = midpoint;
+            }
+        } else {
+            let midpoint = (self.lat_min + self.lat_max) / 2.0;
+            if is_set {
+                self.lat_min = midpoint;
+            } else {
+                self.lat_max = midpoint;
+            }
+        }
+
+        self.longitude_bit = !self.longitude_bit;
+    }
+
+    /// Halves the cell, keeping the half containing a geographic location.
+    ///
+    /// # Arguments
+    ///
+    /// * `lat` - The latitude of the geographic location.
+    /// * `lon` - The longitude of the geographic location.
+    ///
+    /// # Returns
+    ///
+    /// The bit for the half kept; `true` for the upper half.
+    fn narrow_towards(&mut self, lat: Coord, lon: Coord) -> bool {
+        let is_set = if self.longitude_bit {
+            lon > (self.lon_min + self.lon_max) / 2.0
+        } else {
+            lat > (self.lat_min + self.lat_max) / 2.0
+        };
+
+        self.narrow(is_set);
+        is_set
+    }
+
+    /// Returns the `(lat, lon)` at the center of the cell.
+    fn center(&self) -> (Coord, Coord) {
+        (
+            (self.lat_min + self.lat_max) / 2.0,
+            (self.lon_min + self.lon_max) / 2.0,
+        )
+    }
+}
+
+/// The longest geohash precision accepted by `encode`. A geohash of 12 characters already
+/// locates a point to within a few centimeters, beyond the resolution of `f32` coordinates,
+/// so longer geohashes would only add meaningless characters; with the `f64-coords`
+/// feature enabled, up to 16 characters are accepted.
+#[cfg(not(feature = "f64-coords"))]
+pub const MAX_PRECISION: usize = 12;
+
+/// The longest geohash precision accepted by `encode`; 16 as the `f64-coords` feature is enabled.
+#[cfg(feature = "f64-coords")]
+pub const MAX_PRECISION: usize = 16;
+
 /// Encodes a geographic location specified by latitude and longitude into a
 /// geohash string with the given precision.
 ///
@@ -40,79 +133,270 @@ pub enum Direction {
 /// * `lat` - The latitude of the geographic location to encode, ranging from -90.0 to 90.0.
 /// * `lon` - The longitude of the geographic location to encode, ranging from -180.0 to 180.0.
 /// * `precision` - The desired precision of the geohash, represented as the number of characters in the resulting string.
+///   Must be between 1 and `MAX_PRECISION`.
 ///
 /// # Returns
 ///
-/// A string representing the geohash of the specified location with the desired precision.
+/// A string representing the geohash of the specified location with the desired precision,
+/// or `ZpathError::InvalidPrecision` if the precision is zero or greater than `MAX_PRECISION`.
 ///
 /// # Example
 ///
 /// ```
-/// use geohash::encode;
+/// use zpath::geohash::encode;
 ///
 /// let latitude = 37.7749;
 /// let longitude = -122.4194;
 /// let precision = 8;
 ///
-/// let geohash = encode(latitude, longitude, precision);
+/// let geohash = encode(latitude, longitude, precision).unwrap();
 ///
 /// println!("Geohash: {}", geohash); // Example output: "9q8yyk8y"
+/// assert_eq!(geohash.len(), 8);
+///
+/// assert!(encode(latitude, longitude, 0).is_err());
+/// assert!(encode(latitude, longitude, 100).is_err());
 /// ```
-pub fn encode(lat: f32, lon: f32, precision: usize) -> String {
-    let mut geohash = Vec::with_capacity(precision);
+pub fn encode(lat: Coord, lon: Coord, precision: usize) -> Result<String, ZpathError> {
+    if precision == 0 || precision > MAX_PRECISION {
+        return Err(ZpathError::InvalidPrecision(precision));
+    }
 
-    // Initialize latitude and longitude mins / maxes to the entire range of Earth
-    // These values will change as we subdivide the Earth into smaller and smaller pieces
-    let (mut lat_min, mut lat_max) = (-90.0, 90.0);
-    let (mut lon_min, mut lon_max) = (-180.0, 180.0);
+    Ok(encode_unchecked(lat, lon, precision))
+}
 
-    let mut bits = 0; // The 5 binary bits used to determine which base32 char to append next; initially '00000'
-    let mut bit = 0; // Which digit in 'bits' we are currently assigning (from least significant to most)
-    let mut longitude_bit = true; // Alternates between true / false to switch between assigning lon and lat bits
+/// Encodes a geographic location into a geohash in the same manner as `encode`, without
+/// checking the precision. Used within the crate where the precision is known to be valid.
+///
+/// # Arguments
+///
+/// * `lat` - The latitude of the geographic location to encode, ranging from -90.0 to 90.0.
+/// * `lon` - The longitude of the geographic location to encode, ranging from -180.0 to 180.0.
+/// * `precision` - The number of characters in the resulting geohash.
+///
+/// # Returns
+///
+/// A string representing the geohash of the specified location with the desired precision.
+pub(crate) fn encode_unchecked(lat: Coord, lon: Coord, precision: usize) -> String {
+    encode_iter(lat, lon).take(precision).collect()
+}
 
-    while geohash.len() < precision {
-        let midpoint;
+/// Lazily encodes a geographic location into a geohash, one character at a time. Each
+/// character is only computed when requested, so a coarse prefix can be taken without
+/// computing the full geohash, and a single pass can produce the geohash at several
+/// precisions. Every prefix of the characters is the geohash given by `encode` at that
+/// precision.
+///
+/// # Arguments
+///
+/// * `lat` - The latitude of the geographic location to encode, ranging from -90.0 to 90.0.
+/// * `lon` - The longitude of the geographic location to encode, ranging from -180.0 to 180.0.
+///
+/// # Returns
+///
+/// An iterator over the characters of the geohash, ending after `MAX_PRECISION` characters.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::{encode, encode_iter};
+///
+/// let prefix: String = encode_iter(37.7749, -122.4194).take(3).collect();
+/// assert_eq!(prefix, "9q8");
+///
+/// // Collect the geohash at every precision from a single pass
+/// let mut geohash = String::new();
+/// for c in encode_iter(37.7749, -122.4194).take(8) {
+///     geohash.push(c);
+///     assert_eq!(geohash, encode(37.7749, -122.4194, geohash.len()).unwrap());
+/// }
+/// ```
+pub fn encode_iter(lat: Coord, lon: Coord) -> impl Iterator<Item = char> {
+    // Start from the entire range of Earth, subdividing it into smaller and smaller pieces
+    let mut bisection = Bisection::new();
 
-        // Determine whether or not the current digit in bits is a latitude or
-        // longitude bit and whether it should be a '0' or '1'
-        if longitude_bit {
-            midpoint = (lon_min + lon_max) / 2.0;
+    let next_char = move || {
+        let mut bits = 0; // The 5 binary bits used to determine which base32 char to yield next; initially '00000'
 
-            if lon > midpoint {
+        // Assign each of the five bits, from most significant to least
+        for bit in 0..5 {
+            if bisection.narrow_towards(lat, lon) {
                 bits |= 1 << (4 - bit);
-                lon_min = midpoint;
-            } else {
-                lon_max = midpoint;
-            }
-        } else {
-            midpoint = (lat_min + lat_max) / 2.0;
-
-            if lat > midpoint {
-                bits |= 1 << (4 - bit);
-                lat_min = midpoint;
-            } else {
-                lat_max = midpoint;
             }
         }
 
-        // Once 'bits' has all five bits populated, we can translate the constructed binary number into base32
-        // Otherwise, move to the next bit in bits and repeat until full
-        if bit == 4 {
-            geohash.push(BASE_32GHS[bits]); // Push the appropriate 32ghs char to the end of the geohash
-            bits = 0; // Reset bits back to 00000
-            bit = 0; // Reset bit back to least significant digit in bits
-        } else {
-            bit += 1;
+        // Translate the constructed binary number into base32
+        Some(BASE_32GHS[bits] as char)
+    };
+
+    std::iter::from_fn(next_char).take(MAX_PRECISION)
+}
+
+/// Checks that a geohash only contains characters from the geohash alphabet.
+///
+/// # Arguments
+///
+/// * `geohash` - The geohash to check.
+///
+/// # Returns
+///
+/// `Ok(())` if the geohash is valid, otherwise `ZpathError::InvalidGeohash`.
+pub(crate) fn validate(geohash: &str) -> Result<(), ZpathError> {
+    if geohash.bytes().all(|c| BASE_32GHS.contains(&c)) {
+        Ok(())
+    } else {
+        Err(ZpathError::InvalidGeohash(String::from(geohash)))
+    }
+}
+
+/// Decodes a geohash into the bounding box of the cell it represents.
+///
+/// # Arguments
+///
+/// * `geohash` - The geohash to decode.
+///
+/// # Returns
+///
+/// A tuple of `(min_lat, min_lon, max_lat, max_lon)` describing the edges of the cell, or
+/// `ZpathError::InvalidGeohash` if the geohash contains characters outside the geohash alphabet.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::decode_bbox;
+///
+/// let (min_lat, min_lon, max_lat, max_lon) = decode_bbox("9q8yyk8y").unwrap();
+///
+/// assert!(min_lat <= 37.7749 && 37.7749 <= max_lat);
+/// assert!(min_lon <= -122.4194 && -122.4194 <= max_lon);
+/// assert!(decode_bbox("9q8yyka").is_err());
+/// ```
+pub fn decode_bbox(geohash: &str) -> Result<(Coord, Coord, Coord, Coord), ZpathError> {
+    validate(geohash)?;
+
+    let mut bisection = Bisection::new();
+
+    for c in geohash.bytes() {
+        // The geohash has been validated, so every character is in the alphabet
+        let bits = BASE_32GHS.iter().position(|&b| b == c).unwrap();
+
+        // Each character holds five bits, from most significant to least, alternating
+        // between longitude and latitude in the same manner as encode()
+        for bit in (0..5).rev() {
+            bisection.narrow(bits & (1 << bit) != 0);
         }
+    }
+
+    Ok((
+        bisection.lat_min,
+        bisection.lon_min,
+        bisection.lat_max,
+        bisection.lon_max,
+    ))
+}
+
+/// Encodes a geographic location into a Morton (Z-order) code: the same interleaved bits
+/// that make up a geohash, returned as an integer rather than as base32 characters.
+/// Integers are cheaper to compare and store than strings, and sort in the same order as
+/// the equivalent geohashes.
+///
+/// The code occupies the lowest `bits` bits of the result. Counting from the most
+/// significant of those as bit 0, longitude is held in the even bits and latitude in the
+/// odd bits, matching the order in which `encode` assigns them. As each geohash character
+/// holds five bits, a geohash of precision `p` is the same as the Morton code with
+/// `bits = 5 * p`.
+///
+/// # Arguments
+///
+/// * `lat` - The latitude of the geographic location to encode, ranging from -90.0 to 90.0.
+/// * `lon` - The longitude of the geographic location to encode, ranging from -180.0 to 180.0.
+/// * `bits` - The number of interleaved bits in the code, from 0 to 64.
+///
+/// # Returns
+///
+/// The Morton code of the specified location, or `ZpathError::InvalidPrecision` if `bits`
+/// is greater than 64.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::encode_morton;
+///
+/// // "9q8yy" is 01001 10110 01000 11110 11110 in the geohash alphabet
+/// let code = encode_morton(37.7749, -122.4194, 25).unwrap();
+/// assert_eq!(code, 0b01001_10110_01000_11110_11110);
+///
+/// // The first bit halves the longitude range, the second the latitude range
+/// assert_eq!(encode_morton(45.0, 90.0, 2).unwrap(), 0b11);
+/// assert_eq!(encode_morton(45.0, -90.0, 2).unwrap(), 0b01);
+///
+/// assert!(encode_morton(45.0, 90.0, 65).is_err());
+/// ```
+pub fn encode_morton(lat: Coord, lon: Coord, bits: u8) -> Result<u64, ZpathError> {
+    if bits > 64 {
+        return Err(ZpathError::InvalidPrecision(bits as usize));
+    }
 
-        longitude_bit = !longitude_bit;
+    let mut bisection = Bisection::new();
+    let mut code = 0;
+
+    for _ in 0..bits {
+        code = (code << 1) | u64::from(bisection.narrow_towards(lat, lon));
+    }
+
+    Ok(code)
+}
+
+/// Decodes a Morton (Z-order) code produced by `encode_morton` into the center of the cell
+/// it represents.
+///
+/// # Arguments
+///
+/// * `code` - The Morton code to decode, held in its lowest `bits` bits.
+/// * `bits` - The number of interleaved bits in the code, from 0 to 64.
+///
+/// # Returns
+///
+/// A tuple of `(lat, lon)` at the center of the cell, or `ZpathError::InvalidPrecision` if
+/// `bits` is greater than 64.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::{decode_morton, encode_morton};
+///
+/// let code = encode_morton(37.7749, -122.4194, 40).unwrap();
+/// let (lat, lon) = decode_morton(code, 40).unwrap();
+///
+/// assert!((lat - 37.7749).abs() < 0.001);
+/// assert!((lon - -122.4194).abs() < 0.001);
+/// assert_eq!(decode_morton(0, 0).unwrap(), (0.0, 0.0));
+/// assert!(decode_morton(0, 65).is_err());
+/// ```
+pub fn decode_morton(code: u64, bits: u8) -> Result<(Coord, Coord), ZpathError> {
+    if bits > 64 {
+        return Err(ZpathError::InvalidPrecision(bits as usize));
+    }
+
+    let mut bisection = Bisection::new();
+
+    for bit in (0..bits).rev() {
+        bisection.narrow(code & (1 << bit) != 0);
     }
 
-    String::from_utf8(geohash).unwrap()
+    Ok(bisection.center())
 }
 
 /// Finds and returns the geohash of the cell adjacent to the given geohash in the specified direction.
 ///
+/// Cells are not square in terms of their characters' layout: each character subdivides its
+/// parent cell into a grid of 32 children, which is 8 columns by 4 rows for geohashes of
+/// odd length and 4 columns by 8 rows for geohashes of even length. As a result, the
+/// character that changes when stepping to a neighbor depends on both the direction and
+/// the length of the geohash, and stepping north from a cell does not necessarily alter
+/// the same characters as stepping east. When the adjacent cell lies outside of the
+/// current cell's parent, the parent is itself stepped in the same direction.
+///
 /// # Arguments
 ///
 /// * `geohash` - The geohash of the current cell.
@@ -121,25 +405,40 @@ pub fn encode(lat: f32, lon: f32, precision: usize) -> String {
 ///
 /// # Returns
 ///
-/// A string representing the geohash of the adjacent cell.
+/// A string representing the geohash of the adjacent cell. Stepping East or West across
+/// the antimeridian wraps around to the opposite edge of the map, while stepping North or
+/// South past a pole has nowhere to go and returns the given geohash unchanged. Returns
+/// `ZpathError::InvalidGeohash` if the geohash contains characters outside the geohash alphabet.
 ///
 /// # Example
 ///
 /// ```
-/// use geohash::{get_adjacent_cell, Direction};
+/// use zpath::geohash::{get_adjacent_cell, Direction};
 ///
 /// let current_geohash = "u4pruydq";
-/// let adjacent_geohash_north = get_adjacent_cell(current_geohash, Direction::North);
+/// let adjacent_geohash_north = get_adjacent_cell(current_geohash, Direction::North).unwrap();
 ///
 /// println!("Adjacent cell to the North: {}", adjacent_geohash_north); // Example output: "u4pruydr"
+///
+/// // Stepping back in the opposite direction returns to the original cell
+/// assert_eq!(get_adjacent_cell(&adjacent_geohash_north, Direction::South).unwrap(), current_geohash);
+///
+/// // The north-easternmost cell wraps across the antimeridian, but is clamped at the pole
+/// assert_eq!(get_adjacent_cell("zzzz", Direction::East).unwrap(), "bpbp");
+/// assert_eq!(get_adjacent_cell("zzzz", Direction::North).unwrap(), "zzzz");
+/// assert_eq!(get_adjacent_cell("0000", Direction::South).unwrap(), "0000");
+///
+/// assert!(get_adjacent_cell("u4pruyda", Direction::North).is_err());
 /// ```
-fn get_adjacent_cell(geohash: &str, direction: Direction) -> String {
-    if geohash.is_empty() {
-        return String::new();
-    }
+pub fn get_adjacent_cell(geohash: &str, direction: Direction) -> Result<String, ZpathError> {
+    validate(geohash)?;
 
+    // The geohash has been validated as ASCII, so it can be sliced by byte
+    let last_char = match geohash.chars().last() {
+        Some(c) => c,
+        None => return Ok(String::new()),
+    };
     let mut parent_geohash = String::from(&geohash[0..geohash.len() - 1]);
-    let last_char = geohash.chars().last().unwrap();
 
     // Based on the current cell's type (4x8 or 8x4) and the direction of
     // the adjacent cell, determine which set of lookup tables to reference
@@ -161,15 +460,32 @@ fn get_adjacent_cell(geohash: &str, direction: Direction) -> String {
     // In the case that the relevant adjacent cell is not contained within the
     // current cell's parent, we need to alter the parent_geohash to its adjacent
     // counterpart in the relevant direction.
-    if border.contains(&last_char) && !parent_geohash.is_empty() {
-        parent_geohash = get_adjacent_cell(&parent_geohash, direction)
+    if border.contains(&last_char) {
+        if parent_geohash.is_empty() {
+            // A top-level cell on its border in this direction lies on the edge of the map.
+            // The lookup tables wrap East / West across the antimeridian, but there is
+            // nothing beyond the poles, so clamp to the current cell instead.
+            if direction == Direction::North || direction == Direction::South {
+                return Ok(String::from(geohash));
+            }
+        } else {
+            let adjacent_parent = get_adjacent_cell(&parent_geohash, direction)?;
+
+            // The parent was clamped at a pole, so this cell must be clamped too
+            if adjacent_parent == parent_geohash {
+                return Ok(String::from(geohash));
+            }
+
+            parent_geohash = adjacent_parent;
+        }
     }
 
-    // Use the neighbor lookup table to determine which child cell is in the relevant direction
+    // Use the neighbor lookup table to determine which child cell is in the relevant direction.
+    // Each table holds the entire alphabet, so the validated character is always found.
     let index = neighbor.iter().position(|&c| c == last_char).unwrap();
     let adjacent_cell_char = BASE_32GHS[index] as char;
 
-    format!("{}{}", parent_geohash, adjacent_cell_char)
+    Ok(format!("{}{}", parent_geohash, adjacent_cell_char))
 }
 
 /// Finds and returns the geohash strings of all the cells surrounding a given
@@ -181,19 +497,24 @@ fn get_adjacent_cell(geohash: &str, direction: Direction) -> String {
 ///
 /// # Returns
 ///
-/// A vector of strings representing the geohashes of the surrounding cells.
+/// A vector of strings representing the geohashes of the surrounding cells. Cells along
+/// a pole have fewer than eight neighbors, as there are no cells beyond the pole. Returns
+/// `ZpathError::InvalidGeohash` if the geohash contains characters outside the geohash alphabet.
 ///
 /// # Example
 ///
 /// ```
-/// use geohash::{get_surrounding_cells};
+/// use zpath::geohash::{get_surrounding_cells};
 ///
 /// let center_geohash = "u4pruydq";
-/// let surrounding_geohashes = get_surrounding_cells(center_geohash);
+/// let surrounding_geohashes = get_surrounding_cells(center_geohash).unwrap();
 ///
 /// println!("Surrounding cells: {:?}", surrounding_geohashes);
+///
+/// assert_eq!(surrounding_geohashes.len(), 8);
+/// assert_eq!(get_surrounding_cells("zzzz").unwrap().len(), 5);
 /// ```
-pub fn get_surrounding_cells(geohash: &str) -> Vec<String> {
+pub fn get_surrounding_cells(geohash: &str) -> Result<Vec<String>, ZpathError> {
     let directions = [
         Direction::North,
         Direction::East,
@@ -204,15 +525,227 @@ pub fn get_surrounding_cells(geohash: &str) -> Vec<String> {
     let mut adjacent_cells = Vec::with_capacity(8);
 
     for direction in directions {
-        let adjacent = get_adjacent_cell(geohash, direction);
+        let adjacent = get_adjacent_cell(geohash, direction)?;
+
+        // Cells along a pole have no neighbors beyond it; skip the clamped duplicates
+        if adjacent == geohash {
+            continue;
+        }
 
         if direction == Direction::North || direction == Direction::South {
-            adjacent_cells.push(get_adjacent_cell(&adjacent, Direction::East));
-            adjacent_cells.push(get_adjacent_cell(&adjacent, Direction::West));
+            adjacent_cells.push(get_adjacent_cell(&adjacent, Direction::East)?);
+            adjacent_cells.push(get_adjacent_cell(&adjacent, Direction::West)?);
         }
 
         adjacent_cells.push(adjacent);
     }
 
-    adjacent_cells
+    // Very coarse cells can wrap around to meet themselves or each other across the antimeridian
+    let mut unique_cells = Vec::with_capacity(adjacent_cells.len());
+    for cell in adjacent_cells {
+        if cell != geohash && !unique_cells.contains(&cell) {
+            unique_cells.push(cell);
+        }
+    }
+
+    Ok(unique_cells)
+}
+
+/// Finds the geohashes of every cell within a given number of steps of a geohash cell,
+/// forming a square block of cells at the same precision centered on it. Useful for
+/// searching outwards from a point without coarsening the precision.
+///
+/// # Arguments
+///
+/// * `geohash` - The geohash of the center cell.
+/// * `ring` - The number of steps to take outwards in each direction.
+///
+/// # Returns
+///
+/// A vector of the geohashes of the `(2 * ring + 1)²` cells in the block, including the
+/// center cell, ordered row by row from the North-West corner. The block wraps across the
+/// antimeridian, while cells beyond a pole are left out, as are any cells reached more
+/// than once by wrapping at very coarse precisions. Returns `ZpathError::InvalidGeohash`
+/// if the geohash contains characters outside the geohash alphabet.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::{get_neighborhood, get_surrounding_cells};
+///
+/// let block = get_neighborhood("u4pruydq", 2).unwrap();
+/// assert_eq!(block.len(), 25);
+/// assert_eq!(block[12], "u4pruydq");
+///
+/// // The first ring is the center cell and its surrounding cells
+/// let mut first_ring = get_neighborhood("u4pruydq", 1).unwrap();
+/// let mut expected = get_surrounding_cells("u4pruydq").unwrap();
+/// expected.push(String::from("u4pruydq"));
+/// first_ring.sort();
+/// expected.sort();
+/// assert_eq!(first_ring, expected);
+///
+/// // Only the rows South of a cell at the North pole exist
+/// assert_eq!(get_neighborhood("zzzz", 1).unwrap().len(), 6);
+/// ```
+pub fn get_neighborhood(geohash: &str, ring: usize) -> Result<Vec<String>, ZpathError> {
+    validate(geohash)?;
+
+    // Step North to the top row, stopping early at the pole
+    let mut row_center = String::from(geohash);
+    let mut rows_north = 0;
+    while rows_north < ring {
+        let next = get_adjacent_cell(&row_center, Direction::North)?;
+        if next == row_center {
+            break;
+        }
+        row_center = next;
+        rows_north += 1;
+    }
+
+    let mut cells = Vec::with_capacity((2 * ring + 1) * (2 * ring + 1));
+    let mut seen = HashSet::new();
+
+    for _ in 0..=rows_north + ring {
+        let mut cell = row_center.clone();
+        for _ in 0..ring {
+            cell = get_adjacent_cell(&cell, Direction::West)?;
+        }
+
+        for _ in 0..=2 * ring {
+            if seen.insert(cell.clone()) {
+                cells.push(cell.clone());
+            }
+            cell = get_adjacent_cell(&cell, Direction::East)?;
+        }
+
+        row_center = get_adjacent_cell(&row_center, Direction::South)?;
+    }
+
+    Ok(cells)
+}
+
+/// Calculates the dimensions, in degrees, of any geohash cell at the given precision.
+/// Every cell at a given precision covers the same span of latitude and longitude.
+///
+/// # Arguments
+///
+/// * `precision` - The number of characters in the geohashes of the cells.
+///
+/// # Returns
+///
+/// A tuple of `(height, width)`; the degrees of latitude and longitude spanned by a cell.
+pub fn get_cell_size(precision: usize) -> (Coord, Coord) {
+    // Bits alternate between longitude and latitude, starting with longitude
+    let lon_bits = (precision * 5).div_ceil(2);
+    let lat_bits = precision * 5 / 2;
+
+    (
+        180.0 / Coord::powi(2.0, lat_bits as i32),
+        360.0 / Coord::powi(2.0, lon_bits as i32),
+    )
+}
+
+/// Finds and returns the geohashes of every cell at the given precision that overlaps
+/// the bounding box described by the given minimum and maximum coordinates.
+///
+/// # Arguments
+///
+/// * `min_lat` - The southern edge of the bounding box.
+/// * `min_lon` - The western edge of the bounding box.
+/// * `max_lat` - The northern edge of the bounding box.
+/// * `max_lon` - The eastern edge of the bounding box.
+/// * `precision` - The precision of the geohashes to return.
+///
+/// # Returns
+///
+/// A vector of strings representing the geohashes of the covering cells, ordered
+/// from south to north and west to east.
+pub fn get_covering_cells(
+    min_lat: Coord,
+    min_lon: Coord,
+    max_lat: Coord,
+    max_lon: Coord,
+    precision: usize,
+) -> Vec<String> {
+    let (cell_height, cell_width) = get_cell_size(precision);
+    let rows = (180.0 / cell_height) as i64;
+    let cols = (360.0 / cell_width) as i64;
+
+    // A coordinate lying exactly on a cell border is encoded into the lower cell,
+    // so the row / column numbers are found by rounding up and stepping back one
+    let to_cell = |offset: Coord, size: Coord, count: i64| {
+        ((offset / size).ceil() as i64 - 1).clamp(0, count - 1)
+    };
+
+    let (min_row, max_row) = (
+        to_cell(min_lat + 90.0, cell_height, rows),
+        to_cell(max_lat + 90.0, cell_height, rows),
+    );
+    let (min_col, max_col) = (
+        to_cell(min_lon + 180.0, cell_width, cols),
+        to_cell(max_lon + 180.0, cell_width, cols),
+    );
+
+    let mut cells = Vec::new();
+
+    // Encode the center point of every cell in the covered block of rows and columns
+    for row in min_row..=max_row {
+        let lat = -90.0 + (row as Coord + 0.5) * cell_height;
+
+        for col in min_col..=max_col {
+            let lon = -180.0 + (col as Coord + 0.5) * cell_width;
+            cells.push(encode_unchecked(lat, lon, precision));
+        }
+    }
+
+    cells
+}
+
+/// Estimates the dimensions, in kilometers, of the cell represented by a geohash. The cell's
+/// bounding box is decoded and its edges measured with the Haversine formula; the width is
+/// measured across the cell's central latitude, as cells narrow towards the poles.
+///
+/// # Arguments
+///
+/// * `geohash` - The geohash of the cell to measure.
+///
+/// # Returns
+///
+/// A tuple of `(width_km, height_km)` describing the approximate size of the cell, or
+/// `ZpathError::InvalidGeohash` if the geohash contains characters outside the geohash alphabet.
+///
+/// # Example
+///
+/// ```
+/// use zpath::geohash::geohash_cell_dimensions;
+///
+/// let (equator_width, equator_height) = geohash_cell_dimensions("s000").unwrap();
+/// let (arctic_width, arctic_height) = geohash_cell_dimensions("upbp").unwrap();
+///
+/// assert!((equator_width - 39.1).abs() < 0.1 && (equator_height - 19.6).abs() < 0.1);
+/// assert!(arctic_width < equator_width);
+/// assert!((arctic_height - equator_height).abs() < 0.1);
+/// ```
+pub fn geohash_cell_dimensions(geohash: &str) -> Result<(f32, f32), ZpathError> {
+    let (min_lat, min_lon, max_lat, max_lon) = decode_bbox(geohash)?;
+    let center_lat = (min_lat + max_lat) / 2.0;
+    let center_lon = (min_lon + max_lon) / 2.0;
+
+    let width = crate::haversine_distance(
+        center_lat,
+        min_lon,
+        center_lat,
+        max_lon,
+        crate::EARTH_RADIUS,
+    );
+    let height = crate::haversine_distance(
+        min_lat,
+        center_lon,
+        max_lat,
+        center_lon,
+        crate::EARTH_RADIUS,
+    );
+
+    Ok((width, height))
 }
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A value within a flat JSON object. Nested objects and arrays aren't supported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+/// Parses a single JSON object whose values are all strings, numbers, booleans or null, such
+/// as a record from a JSON Lines file. Later duplicate keys replace earlier ones.
+///
+/// # Arguments
+///
+/// * `text` - The JSON text of the object.
+///
+/// # Returns
+///
+/// A map from each key in the object to its value, or a description of why the text isn't
+/// a valid flat JSON object.
+pub fn parse_flat_object(text: &str) -> Result<HashMap<String, JsonValue>, String> {
+    let mut chars = text.chars().peekable();
+    let mut object = HashMap::new();
+
+    skip_whitespace(&mut chars);
+    expect(&mut chars, '{')?;
+    skip_whitespace(&mut chars);
+
+    if chars.peek() == Some(&'}') {
+        chars.next();
+    } else {
+        loop {
+            skip_whitespace(&mut chars);
+            let key = parse_string(&mut chars)?;
+            skip_whitespace(&mut chars);
+            expect(&mut chars, ':')?;
+            skip_whitespace(&mut chars);
+            let value = parse_value(&mut chars)?;
+            object.insert(key, value);
+            skip_whitespace(&mut chars);
+
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => break,
+                Some(c) => return Err(format!("expected ',' or '}}' but found '{}'", c)),
+                None => return Err(String::from("unexpected end of object")),
+            }
+        }
+    }
+
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(object),
+        Some(c) => Err(format!("unexpected '{}' after the end of the object", c)),
+    }
+}
+
+/// Advances past any whitespace.
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Consumes the next character, which must be `expected`.
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(format!("expected '{}' but found '{}'", expected, c)),
+        None => Err(format!(
+            "expected '{}' but found the end of the line",
+            expected
+        )),
+    }
+}
+
+/// Parses a value, beginning at its first character.
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    match chars.peek() {
+        Some('"') => parse_string(chars).map(JsonValue::String),
+        Some('{') | Some('[') => Err(String::from("nested objects and arrays aren't supported")),
+        Some(c) if c.is_ascii_alphabetic() => {
+            let word: String =
+                std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_alphabetic())).collect();
+
+            match word.as_str() {
+                "true" => Ok(JsonValue::Bool(true)),
+                "false" => Ok(JsonValue::Bool(false)),
+                "null" => Ok(JsonValue::Null),
+                _ => Err(format!("unexpected '{}'", word)),
+            }
+        }
+        Some(_) => {
+            let number: String = std::iter::from_fn(|| {
+                chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+            })
+            .collect();
+
+            number
+                .parse()
+                .map(JsonValue::Number)
+                .map_err(|_| format!("invalid number '{}'", number))
+        }
+        None => Err(String::from(
+            "expected a value but found the end of the line",
+        )),
+    }
+}
+
+/// Parses a string, beginning at its opening quote, and resolves any escape sequences.
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let mut string = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some('/') => string.push('/'),
+                Some('b') => string.push('\u{8}'),
+                Some('f') => string.push('\u{c}'),
+                Some('n') => string.push('\n'),
+                Some('r') => string.push('\r'),
+                Some('t') => string.push('\t'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&hex, 16)
+                        .map_err(|_| format!("invalid unicode escape '\\u{}'", hex))?;
+
+                    // Characters outside the basic multilingual plane (written as surrogate
+                    // pairs) are replaced rather than decoded
+                    string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                Some(c) => return Err(format!("invalid escape '\\{}'", c)),
+                None => return Err(String::from("unterminated string")),
+            },
+            Some(c) => string.push(c),
+            None => return Err(String::from("unterminated string")),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{to_unit_vector, Connection, Coord, SpatialIndex, Waypoint, EARTH_RADIUS};
+use std::collections::BinaryHeap;
+
+/// A node of a `KdTree`, holding a single waypoint and the subtrees on either side of it.
+struct KdNode {
+    point: [Coord; 3],
+    waypoint_index: usize,
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// A k-d tree over the positions of a set of waypoints, offered as an alternative to the
+/// geohash index for nearest neighbor and radius queries.
+///
+/// Waypoints are stored as points on the unit sphere in 3D space rather than as latitude
+/// and longitude, so there are no special cases at the poles or the antimeridian, and the
+/// straight-line (chord) distance between two points always orders them the same way as
+/// the great-circle distance. Unlike the geohash index, query times don't depend on how
+/// evenly the waypoints are spread out.
+///
+/// The tree holds a snapshot of the waypoints it was built from, leaving out any that
+/// aren't `active`; it must be rebuilt with `KdTree::build` after waypoints are added or
+/// moved, or after `Dataset::activate` or `Dataset::deactivate`.
+pub struct KdTree {
+    nodes: Vec<KdNode>,
+    root: Option<usize>,
+}
+
+impl KdTree {
+    /// Builds a balanced k-d tree over the given waypoints.
+    ///
+    /// # Arguments
+    ///
+    /// * `waypoints` - The waypoints to build the tree over; typically `dataset.waypoints`.
+    ///
+    /// # Returns
+    ///
+    /// A new `KdTree` over the active waypoints. Query results are indices into `waypoints`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::kd_tree::KdTree;
+    /// use zpath::SpatialIndex;
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 179.9).unwrap();
+    /// dataset.add_new_waypoint(0.0, -179.9).unwrap();
+    /// dataset.add_new_waypoint(0.0, 170.0).unwrap();
+    ///
+    /// let tree = KdTree::build(&dataset.waypoints);
+    ///
+    /// // The nearest waypoint lies across the antimeridian
+    /// assert_eq!(tree.nearest(0.0, 179.95, 2), vec![0, 1]);
+    /// assert_eq!(tree.within(0.0, 179.95, 100.0).len(), 2);
+    ///
+    /// // Deactivated waypoints are left out, as they are by the geohash index
+    /// dataset.deactivate(0);
+    /// let tree = KdTree::build(&dataset.waypoints);
+    /// assert_eq!(tree.nearest(0.0, 179.95, 1), vec![1]);
+    /// assert_eq!(tree.nearest(0.0, 179.95, 1), dataset.nearest(0.0, 179.95, 1));
+    /// assert_eq!(tree.within(0.0, 179.9, 10.0), dataset.within(0.0, 179.9, 10.0));
+    /// assert!(tree.within(0.0, 179.9, 10.0).is_empty());
+    /// ```
+    pub fn build(waypoints: &[Waypoint]) -> Self {
+        let mut points: Vec<([Coord; 3], usize)> = waypoints
+            .iter()
+            .enumerate()
+            .filter(|(_, waypoint)| waypoint.active)
+            .map(|(index, waypoint)| (to_unit_vector(waypoint.lat, waypoint.lon), index))
+            .collect();
+
+        let mut tree = KdTree {
+            nodes: Vec::with_capacity(points.len()),
+            root: None,
+        };
+        tree.root = tree.build_recursive(&mut points);
+        tree
+    }
+
+    /// Builds the subtree over the given points by splitting them at the median along the
+    /// axis on which they're most spread out.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The unit vectors and waypoint indices of the points in the subtree.
+    ///
+    /// # Returns
+    ///
+    /// The position of the subtree's root within `nodes`, or `None` if there are no points.
+    fn build_recursive(&mut self, points: &mut [([Coord; 3], usize)]) -> Option<usize> {
+        if points.is_empty() {
+            return None;
+        }
+
+        let axis = (0..3)
+            .map(|axis| {
+                let (min, max) = points.iter().fold(
+                    (Coord::INFINITY, Coord::NEG_INFINITY),
+                    |(min, max), (point, _)| (min.min(point[axis]), max.max(point[axis])),
+                );
+                (axis, max - min)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(axis, _)| axis);
+
+        let median = points.len() / 2;
+        points.select_nth_unstable_by(median, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+        let node_position = self.nodes.len();
+        self.nodes.push(KdNode {
+            point: points[median].0,
+            waypoint_index: points[median].1,
+            axis,
+            left: None,
+            right: None,
+        });
+
+        let (lower, upper) = points.split_at_mut(median);
+        let left = self.build_recursive(lower);
+        let right = self.build_recursive(&mut upper[1..]);
+        self.nodes[node_position].left = left;
+        self.nodes[node_position].right = right;
+
+        Some(node_position)
+    }
+
+    /// Recursively collects the `k` points nearest to the query point into a max-heap,
+    /// skipping any subtree that can't contain a point nearer than the current k-th nearest.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The position of the subtree's root within `nodes`.
+    /// * `query` - The unit vector of the query point.
+    /// * `k` - The number of points to collect.
+    /// * `heap` - The nearest points found so far, keyed by squared chord distance.
+    fn nearest_recursive(
+        &self,
+        node: Option<usize>,
+        query: &[Coord; 3],
+        k: usize,
+        heap: &mut BinaryHeap<Connection>,
+    ) {
+        let Some(position) = node else {
+            return;
+        };
+        let node = &self.nodes[position];
+
+        let distance = squared_distance(&node.point, query) as f32;
+        if heap.len() < k {
+            heap.push(Connection::new(node.waypoint_index, distance));
+        } else if heap
+            .peek()
+            .is_some_and(|farthest| distance < farthest.distance)
+        {
+            heap.pop();
+            heap.push(Connection::new(node.waypoint_index, distance));
+        }
+
+        let offset = query[node.axis] - node.point[node.axis];
+        let (near, far) = if offset < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+
+        self.nearest_recursive(near, query, k, heap);
+
+        // Every point beyond the splitting plane is at least `offset` away from the query
+        let plane_distance: Coord = offset * offset;
+        if heap.len() < k
+            || heap
+                .peek()
+                .is_some_and(|farthest| (plane_distance as f32) < farthest.distance)
+        {
+            self.nearest_recursive(far, query, k, heap);
+        }
+    }
+
+    /// Recursively collects every point within a given chord distance of the query point.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The position of the subtree's root within `nodes`.
+    /// * `query` - The unit vector of the query point.
+    /// * `max_squared_distance` - The squared chord distance to search within (inclusive).
+    /// * `found` - The waypoint indices found so far.
+    fn within_recursive(
+        &self,
+        node: Option<usize>,
+        query: &[Coord; 3],
+        max_squared_distance: Coord,
+        found: &mut Vec<usize>,
+    ) {
+        let Some(position) = node else {
+            return;
+        };
+        let node = &self.nodes[position];
+
+        if squared_distance(&node.point, query) <= max_squared_distance {
+            found.push(node.waypoint_index);
+        }
+
+        let offset = query[node.axis] - node.point[node.axis];
+        if offset < 0.0 || offset * offset <= max_squared_distance {
+            self.within_recursive(node.left, query, max_squared_distance, found);
+        }
+        if offset >= 0.0 || offset * offset <= max_squared_distance {
+            self.within_recursive(node.right, query, max_squared_distance, found);
+        }
+    }
+}
+
+impl SpatialIndex for KdTree {
+    fn nearest(&self, lat: Coord, lon: Coord, k: usize) -> Vec<usize> {
+        let mut heap: BinaryHeap<Connection> = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.nearest_recursive(self.root, &to_unit_vector(lat, lon), k, &mut heap);
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|connection| connection.waypoint_index)
+            .collect()
+    }
+
+    fn within(&self, lat: Coord, lon: Coord, radius_km: f32) -> Vec<usize> {
+        // Convert the great-circle radius into the length of the chord it subtends; a
+        // radius reaching the far side of the Earth covers every point on the sphere
+        let angle = (radius_km / EARTH_RADIUS).min(std::f32::consts::PI) as Coord;
+        let chord = 2.0 * (angle / 2.0).sin();
+
+        let mut found = Vec::new();
+        self.within_recursive(
+            self.root,
+            &to_unit_vector(lat, lon),
+            chord * chord,
+            &mut found,
+        );
+        found.sort_unstable();
+        found
+    }
+}
+
+/// Calculates the squared straight-line distance between two points in 3D space.
+fn squared_distance(a: &[Coord; 3], b: &[Coord; 3]) -> Coord {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
@@ -1,728 +1,6995 @@
-mod geohash;
+pub mod geohash;
+mod json;
+pub mod kd_tree;
 mod pseudo_random;
 
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The Earth's equatorial radius in kilometers; used by `Waypoint::get_distance_to` and
+/// throughout the crate. Being the Earth's largest radius, it slightly overestimates most
+/// distances.
+pub const EARTH_RADIUS: f32 = 6378.137;
+
+/// The Earth's mean radius in kilometers. Produces more accurate distances on average than
+/// `EARTH_RADIUS`; see `Waypoint::get_distance_with_radius`.
+pub const MEAN_EARTH_RADIUS: f32 = 6371.0;
+
+/// The number of characters in each waypoint's geohash; cells at this precision are
+/// roughly 38m by 19m.
+const GEOHASH_PRECISION: usize = 8;
+
+/// The most entries a route search preallocates room for in its open set and score maps.
+/// Searches on datasets smaller than this preallocate room for every waypoint; larger
+/// searches grow as needed, so that short routes across huge datasets don't pay for it.
+const SEARCH_CAPACITY_HINT: usize = 4096;
+
+/// The bytes identifying a geohash index written by `Dataset::save_index`.
+const INDEX_MAGIC: &[u8; 4] = b"ZPI1";
+
+/// The floating point type used to store coordinates. This is `f32` by default, which holds
+/// roughly 7 significant digits; at longitudes near ±180° that leaves only around a meter of
+/// resolution, which is plenty for most uses but lossy for surveying or for round-tripping
+/// precise coordinates. Enabling the `f64-coords` feature switches coordinates, and the
+/// trigonometry performed on them, to `f64` at the cost of twice the memory per coordinate.
+/// Distances are always reported as `f32` kilometers.
+#[cfg(not(feature = "f64-coords"))]
+pub type Coord = f32;
+
+/// The floating point type used to store coordinates; `f64` as the `f64-coords` feature is enabled.
+#[cfg(feature = "f64-coords")]
+pub type Coord = f64;
 
 /// Represents a geospatial waypoint with latitude, longitude, a label, geohash, and connections.
+///
+/// Arbitrary attributes (e.g. a category or opening hours) can be attached to a waypoint
+/// through its `metadata`, which routing and indexing ignore.
+///
+/// A waypoint that isn't `active` has been soft-deleted (see `Dataset::deactivate`): it
+/// keeps its place in the dataset, so the indices of other waypoints and connections stay
+/// valid, but spatial queries (nearest neighbor, radius, bounding box, and the like) never
+/// return it, and routes and reachability searches never pass through it.
+///
+/// Waypoints compare equal (`==`) when their labels are equal, regardless of where they
+/// are, as labels identify waypoints within a dataset. Two waypoints at the same position
+/// with different labels are unequal, and two distant waypoints sharing a label are equal;
+/// use `approx_eq` to compare waypoints by position instead.
 #[derive(Debug, Clone)]
 pub struct Waypoint {
-    pub lat: f32,
-    pub lon: f32,
+    pub lat: Coord,
+    pub lon: Coord,
     pub label: String,
     pub geohash: String,
     pub connections: Vec<Connection>,
+    pub metadata: HashMap<String, String>,
+    pub active: bool,
 }
 
-/// Represents a connection between waypoints with a distance and a waypoint index.
+/// Represents a connection between waypoints with a distance, a weight, and a waypoint index.
+///
+/// The `distance` is the geographic distance between the waypoints in kilometers, while the
+/// `weight` is the cost of travelling the connection when routing (e.g. travel time or a
+/// penalty), which defaults to the distance. Routing estimates the remaining cost using
+/// geographic distance, so it only guarantees the shortest route when every connection's
+/// `weight` is at least its geographic distance.
+///
+/// A connection may also carry the speed it can be travelled at, in kilometers per hour,
+/// which is used to estimate travel times along routes. Generated connections have none.
 #[derive(Debug, Clone)]
 pub struct Connection {
     pub distance: f32,
+    pub weight: f32,
     pub waypoint_index: usize,
+    pub speed_kph: Option<f32>,
 }
 
 /// Represents a Trie data structure for indexing waypoints based on geohash prefixes.
+///
+/// The Trie is compressed as a radix tree: each node holds the label of the edge leading to
+/// it, which may be several characters long, so chains of nodes with only a single child are
+/// collapsed into one. Children are keyed by the first character of their edge label.
+/// Waypoints sharing the same geohash are all held by the node at the end of that geohash.
 pub struct Trie {
+    label: String,
     children: HashMap<char, Trie>,
-    waypoint_index: Option<usize>,
+    waypoint_indices: Vec<usize>,
+}
+
+/// Represents a lazy, depth-first traversal over the waypoint indices held within a Trie.
+struct TrieIter<'a> {
+    stack: Vec<&'a Trie>,
+    pending: std::slice::Iter<'a, usize>,
 }
 
-/// Represents a node used in the A* algorithm for pathfinding, with an F score and waypoint index.
+/// Represents a node used in the A* algorithm for pathfinding, with an F score, G score, and waypoint index.
+///
+/// Nodes are ordered so that the open set pops the lowest F score first. Ties are broken by
+/// preferring the highest G score (the node furthest along its route, and so likely closest
+/// to the goal), then the lowest waypoint index, so that searches are fully deterministic.
 struct AStarNode {
     f_score: f32,
+    g_score: f32,
     waypoint_index: usize,
 }
 
+/// Represents the state left behind by an A* search: the cheapest known cost of reaching each
+/// waypoint it visited, the waypoint each was reached from, and the waypoint it stopped at.
+struct AStarSearch {
+    came_from: HashMap<usize, usize>,
+    g_scores: HashMap<usize, f32>,
+    stopped_at: Option<usize>,
+}
+
 /// Represents a dataset of waypoints and geospatial data.
+///
+/// The `geohash_index` is built from each waypoint's geohash as it's added to the dataset.
+/// Any direct mutation of `waypoints` (e.g. correcting a waypoint's coordinates) leaves the
+/// index stale, and must be followed by a call to `rebuild_index`.
+///
+/// Distances between waypoints for nearest neighbor searches and the routing heuristic are
+/// measured with the dataset's `Metric`, which is the great-circle distance unless set with
+/// `set_metric`. Great-circle distances are measured on a sphere of the dataset's Earth
+/// radius, which is `EARTH_RADIUS` unless set with `set_earth_radius`.
+///
+/// Connections lead from one waypoint to another and are followed in that direction only.
+/// When `directed` is `true` (the default), the `assign_*` methods give each waypoint
+/// connections to its own nearest neighbors, which needn't connect back to it. When it's
+/// `false`, the graph is treated as undirected: the `assign_*` methods match every
+/// connection they create with one in the opposite direction, and
+/// searches which would otherwise need to follow connections backwards skip doing so.
 pub struct Dataset {
     pub waypoints: Vec<Waypoint>,
     pub geohash_index: Trie,
+    pub directed: bool,
+    metric: Option<Box<dyn Metric + Send + Sync>>,
+    earth_radius: f32,
+    precision: usize,
+    requested_connections: usize,
 }
 
-impl PartialEq for Waypoint {
-    fn eq(&self, other: &Self) -> bool {
-        self.label == other.label
+/// Selects which pseudorandom number generator is used when generating waypoints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RngKind {
+    /// A generator based on the XorShift algorithm; the default.
+    XorShift,
+    /// A generator based on the Linear Congruential Generator (LCG) algorithm.
+    Lcg,
+}
+
+/// Selects how labels are given to generated waypoints. Each scheme labels a waypoint from
+/// its index in the dataset, so labels are unique within a dataset and stable across runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelScheme {
+    /// Letters as in spreadsheet columns: A, B, ..., Z, AA, AB, ...; the default. See
+    /// `Waypoint::generate_label`.
+    Alpha,
+    /// The index, padded with leading zeros to at least `width` digits, e.g. "0042".
+    Numeric { width: usize },
+    /// The index after a prefix, padded with leading zeros to at least 4 digits, e.g.
+    /// "WP-0042".
+    Prefixed(String),
+}
+
+/// Collects the settings for generating a dataset and assigning its connections in one
+/// call to `Dataset::build`. Each setting is chosen with a chained method, and any left
+/// unset keeps its default: no waypoints, a geohash precision of 8, the XorShift generator
+/// seeded from the clock, and no connections.
+///
+/// # Example
+///
+/// ```
+/// use zpath::{DatasetConfig, RngKind};
+///
+/// let config = DatasetConfig::new()
+///     .waypoints(500)
+///     .precision(6)
+///     .rng(RngKind::Lcg)
+///     .seed(42)
+///     .connections(3)
+///     .symmetric(true);
+///
+/// let dataset = zpath::Dataset::build(config).unwrap();
+/// assert_eq!(dataset.waypoints.len(), 500);
+/// assert!(dataset.waypoints.iter().all(|waypoint| waypoint.geohash.len() == 6));
+/// assert!(dataset.graph_metrics().symmetric);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatasetConfig {
+    waypoints: usize,
+    precision: usize,
+    rng: RngKind,
+    seed: Option<u64>,
+    connections: usize,
+    symmetric: bool,
+}
+
+impl Default for DatasetConfig {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl Eq for Waypoint {}
+impl DatasetConfig {
+    /// Creates a configuration with every setting at its default.
+    pub fn new() -> Self {
+        DatasetConfig {
+            waypoints: 0,
+            precision: GEOHASH_PRECISION,
+            rng: RngKind::XorShift,
+            seed: None,
+            connections: 0,
+            symmetric: false,
+        }
+    }
 
-impl Waypoint {
-    /// Calculates and returns the great-circle distance between this waypoint
-    /// and a target waypoint in kilometers using the Haversine formula.
+    /// Sets the number of waypoints to generate.
+    pub fn waypoints(mut self, amt: usize) -> Self {
+        self.waypoints = amt;
+        self
+    }
+
+    /// Sets the precision of the geohashes the waypoints are indexed by, between 1 and
+    /// `geohash::MAX_PRECISION`. Shorter geohashes make for a shallower index with more
+    /// waypoints in each cell.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the pseudorandom number generator used to produce coordinates.
+    pub fn rng(mut self, kind: RngKind) -> Self {
+        self.rng = kind;
+        self
+    }
+
+    /// Sets the seed for the generator, so that the same waypoints are generated every time.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the number of nearest neighbors (K) each waypoint is connected to.
+    pub fn connections(mut self, k: usize) -> Self {
+        self.connections = k;
+        self
+    }
+
+    /// Sets whether every connection is matched by one in the opposite direction, as
+    /// `Dataset::normalize_connections` does. A symmetric dataset is built with `directed`
+    /// set to `false`, so connections assigned to it later are symmetric too.
+    pub fn symmetric(mut self, symmetric: bool) -> Self {
+        self.symmetric = symmetric;
+        self
+    }
+}
+
+/// Records how much work a route search required, for comparing heuristics and routers.
+///
+/// Whenever a cheaper route to a waypoint is found, the search adds another entry for it to
+/// the open set rather than updating the existing one. The outdated entries are skipped
+/// when they come out of the open set rather than being expanded again, and are counted in
+/// `stale_entries_skipped`.
+#[derive(Debug, Clone, Default)]
+pub struct SearchStats {
+    pub nodes_expanded: usize,
+    pub max_open_set_size: usize,
+    pub stale_entries_skipped: usize,
+    pub path_cost: Option<f32>,
+}
+
+/// One waypoint along a route profile produced by `Dataset::route_profile`. Distances are
+/// in kilometers. `heading_change` is the turn made at the waypoint, in degrees from -180.0
+/// to 180.0: positive for a right turn and negative for a left turn, and `0.0` at either end
+/// of the route.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct RouteStep {
+    pub waypoint_index: usize,
+    pub cumulative_distance: f32,
+    pub segment_distance: f32,
+    pub heading_change: f32,
+}
+
+/// Units of distance that results can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceUnit {
+    /// Kilometers; the unit used throughout the crate.
+    Km,
+    /// Statute miles.
+    Mi,
+    /// Meters.
+    M,
+    /// Nautical miles.
+    NauticalMi,
+}
+
+/// Summarizes the connections assigned across a dataset, with the minimum, maximum, and
+/// mean connection distance and the number of waypoints left with fewer connections than requested.
+#[derive(Debug, Clone)]
+pub struct ConnectionStats {
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub mean_distance: f32,
+    pub connection_count: usize,
+    pub under_connected: usize,
+}
+
+/// Summarizes the shape of a dataset's connection graph. When every connection has a matching
+/// connection in the opposite direction, the graph is treated as undirected and each pair of
+/// connections is counted as a single edge.
+#[derive(Debug, Clone)]
+pub struct GraphMetrics {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub total_edge_length: f32,
+    pub average_degree: f32,
+    pub symmetric: bool,
+}
+
+/// The errors that can be returned by the fallible operations in this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZpathError {
+    /// A waypoint was given that isn't part of the dataset. Holds the waypoint's label.
+    WaypointNotFound(String),
+    /// A geohash contained characters outside the geohash alphabet. Holds the geohash.
+    InvalidGeohash(String),
+    /// A geohash precision was zero or greater than `geohash::MAX_PRECISION`, or a Morton
+    /// code was given more than 64 bits. Holds the precision.
+    InvalidPrecision(usize),
+    /// A line of an imported file couldn't be read as a waypoint. Holds the line number
+    /// (starting from 1) and the reason.
+    MalformedRecord(usize, String),
+    /// A latitude or longitude wasn't a finite number. Holds the latitude and longitude.
+    InvalidCoordinates(Coord, Coord),
+}
+
+/// A measure of the distance between two waypoints. Datasets use a metric for nearest
+/// neighbor searches and to estimate the remaining distance when routing, which allows
+/// the crate to be used with maps that aren't of the Earth (e.g. a flat game map).
+///
+/// The geohash index always treats coordinates as latitude and longitude on the Earth, so
+/// searches that rely on it, such as `Dataset::get_knn_geohash`, are only exact for metrics
+/// that agree with `Haversine` about which waypoints are nearer; `Dataset::get_knn_naive`
+/// is exact for any metric.
+///
+/// # Example
+///
+/// ```
+/// use zpath::{Metric, Waypoint};
+///
+/// // Great-circle distances as angles, in units far smaller than kilometers
+/// struct Radians;
+///
+/// impl Metric for Radians {
+///     fn distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+///         a.get_distance_to(b) / zpath::EARTH_RADIUS
+///     }
+/// }
+///
+/// let mut dataset = zpath::Dataset::new();
+/// dataset.generate_waypoints_with(500, zpath::RngKind::XorShift, 42);
+/// dataset.set_metric(Radians);
+///
+/// for index in 0..dataset.waypoints.len() {
+///     let geohash = dataset.get_knn_geohash_by_index(index, 3);
+///     let naive = dataset.get_knn_naive_by_index(index, 3);
+///     assert_eq!(geohash.len(), naive.len());
+///     for (a, b) in geohash.iter().zip(&naive) {
+///         assert_eq!(a.distance, b.distance);
+///     }
+/// }
+/// ```
+pub trait Metric {
+    /// Measures the distance between two waypoints.
     ///
     /// # Arguments
     ///
-    /// * `target` - A reference to the target `Waypoint` to get the distance to.
+    /// * `a` - A reference to the first `Waypoint`.
+    /// * `b` - A reference to the second `Waypoint`.
     ///
     /// # Returns
     ///
-    /// The great-circle distance in kilometers between this waypoint and the target waypoint.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// let waypoint1 = Waypoint { lat: 37.7749, lon: -122.4194, label: String::from("A"), geohash: String::from("u4pruydq"), connections: Vec::new() };
-    /// let waypoint2 = Waypoint { lat: 34.0522, lon: -118.2437, label: String::from("B"), geohash: String::from("9q5x9p6y"), connections: Vec::new() };
-    ///
-    /// let distance_km = waypoint1.get_distance_to(&waypoint2);
-    ///
-    /// println!("Distance between waypoints: {} km", distance_km);
-    /// ```
-    pub fn get_distance_to(&self, target: &Waypoint) -> f32 {
-        const EARTH_RADIUS: f32 = 6378.137;
-
-        let lat1 = self.lat.to_radians();
-        let lat2 = target.lat.to_radians();
-
-        let dlat = lat2 - lat1;
-        let dlon = target.lon.to_radians() - self.lon.to_radians();
-
-        let a = (dlat / 2.0).sin().powi(2) + (dlon / 2.0).sin().powi(2) * lat1.cos() * lat2.cos();
-        let c = 2.0 * a.sqrt().asin();
+    /// The distance between the waypoints. Should never be negative, and should be the same
+    /// in either direction.
+    fn distance(&self, a: &Waypoint, b: &Waypoint) -> f32;
+}
 
-        EARTH_RADIUS * c
-    }
+/// The great-circle distance in kilometers, as calculated by `Waypoint::get_distance_to`.
+/// This is how datasets measure distance by default, except that a dataset uses its own
+/// Earth radius (see `Dataset::set_earth_radius`) where this always uses `EARTH_RADIUS`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Haversine;
 
-    /// Generates sequential labels to act as unique identifiers based on an
-    /// integer value and a total count. Will generate labels 'A' through 'Z'
-    /// first, then 'AA', 'AB', etc. through 'ZZ', then 'AAA'...
+/// A spatial index over the waypoints of a dataset, answering nearest neighbor and radius
+/// queries about arbitrary coordinates. `Dataset` implements this using its geohash index;
+/// `kd_tree::KdTree` is an alternative that performs more predictably when waypoints are
+/// heavily clustered or very sparse.
+pub trait SpatialIndex {
+    /// Finds the `k` waypoints nearest to the given coordinates.
     ///
     /// # Arguments
     ///
-    /// * `n` - The integer value that represents the waypoint's index in the dataset
-    /// * `total_amt` - The total amount of waypoints for which labels are being generated.
+    /// * `lat` - The latitude of the point to search around, in degrees.
+    /// * `lon` - The longitude of the point to search around, in degrees.
+    /// * `k` - The number of waypoints to find.
     ///
     /// # Returns
     ///
-    /// A string representing the generated label.
+    /// The indices of up to `k` waypoints, sorted from nearest to farthest.
+    fn nearest(&self, lat: Coord, lon: Coord, k: usize) -> Vec<usize>;
+
+    /// Finds every waypoint within a given distance of the given coordinates.
     ///
-    /// # Example
+    /// # Arguments
     ///
-    /// ```
-    /// let n1 = 25;
-    /// let n2 = 27
-    /// let total_amt = 32;
+    /// * `lat` - The latitude of the point to search around, in degrees.
+    /// * `lon` - The longitude of the point to search around, in degrees.
+    /// * `radius_km` - The maximum distance from the point in kilometers (inclusive).
     ///
-    /// let label_1 = generate_label(n1, total_amt);
-    /// let label_2 = generate_label(n2, total_amt);
+    /// # Returns
     ///
-    /// println!("{}", label_1); // Example output: 'Z'
-    /// println!("{}", label_2); // Example output: 'AB'
-    /// ```
-    pub fn generate_label(n: usize) -> String {
-        let mut result = String::new();
-        let mut remaining = n + 1;
+    /// The indices of the waypoints within the radius, sorted in ascending order.
+    fn within(&self, lat: Coord, lon: Coord, radius_km: f32) -> Vec<usize>;
+}
 
-        while remaining > 0 {
-            let remainder = (remaining - 1) % 26;
-            result.push((b'A' + remainder as u8) as char);
-            remaining = (remaining - 1) / 26;
-        }
+/// Calculates the great-circle distance between two coordinates using the Haversine formula,
+/// on a sphere of the given radius. The result is in the same unit as the radius.
+///
+/// # Arguments
+///
+/// * `lat1`, `lon1` - The coordinates of the first point in degrees.
+/// * `lat2`, `lon2` - The coordinates of the second point in degrees.
+/// * `earth_radius` - The radius of the Earth to use.
+///
+/// # Returns
+///
+/// The great-circle distance between the two points.
+pub(crate) fn haversine_distance(
+    lat1: Coord,
+    lon1: Coord,
+    lat2: Coord,
+    lon2: Coord,
+    earth_radius: f32,
+) -> f32 {
+    let lat1 = lat1.to_radians();
+    let lat2 = lat2.to_radians();
 
-        result.chars().rev().collect()
-    }
+    let dlat = lat2 - lat1;
+    let dlon = lon2.to_radians() - lon1.to_radians();
 
-    /// Converts the latitude and longitude coordinates of a waypoint into a
-    /// more readable Degree-Minute-Second (DMS) format string.
-    ///
-    /// # Returns
-    ///
-    /// A string representing the coordinates in DMS format.
-    /// For example, "37°45'30.00\"N, 122°25'12.00\"W".
-    pub fn get_dms(&self) -> String {
-        let lat = self.lat.abs(); // Convert (-) values to (+) for cleaner code; sign only relevant in determining direction
-        let lat_degrees = lat.floor(); // The whole number portion of the value equals degrees
-        let lat_minutes = (lat - lat_degrees) * 60.0; // The decimal portion of the value, times 60, equals minutes
-        let lat_seconds = (lat_minutes - lat_minutes.floor()) * 60.0; // The decimal portion of minutes, times 60, equals seconds
-        let lat_direction = if self.lat >= 0.0 { 'N' } else { 'S' }; // Assign the cardinal direction based on sign
+    let a = (dlat / 2.0).sin().powi(2) + (dlon / 2.0).sin().powi(2) * lat1.cos() * lat2.cos();
+    let c: Coord = 2.0 * a.sqrt().asin();
 
-        let long = self.lon.abs();
-        let long_degrees = long.floor();
-        let long_minutes = (long - long_degrees) * 60.0;
-        let long_seconds = (long_minutes - long_minutes.floor()) * 60.0;
-        let long_direction = if self.lon >= 0.0 { 'E' } else { 'W' };
+    earth_radius * c as f32
+}
 
-        format!(
-            "{}°{}'{:.2}\"{}, {}°{}'{:.2}\"{}",
-            lat_degrees,
-            lat_minutes.floor(),
-            lat_seconds,
-            lat_direction,
-            long_degrees,
-            long_minutes.floor(),
-            long_seconds,
-            long_direction
-        )
-    }
+/// Converts coordinates into the equivalent point on the unit sphere in 3D space, with the
+/// x axis through (0°, 0°), the y axis through (0°, 90°E) and the z axis through the North Pole.
+///
+/// # Arguments
+///
+/// * `lat` - The latitude of the point in degrees.
+/// * `lon` - The longitude of the point in degrees.
+///
+/// # Returns
+///
+/// The `[x, y, z]` components of the point's unit vector.
+pub(crate) fn to_unit_vector(lat: Coord, lon: Coord) -> [Coord; 3] {
+    let lat = lat.to_radians();
+    let lon = lon.to_radians();
+
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
 }
 
-impl PartialEq for Connection {
-    fn eq(&self, other: &Self) -> bool {
-        self.waypoint_index == other.waypoint_index
+/// Densifies the great-circle path between two waypoints into a series of points, such that
+/// no two consecutive points are more than a given distance apart. Drawing straight lines
+/// between the points then closely follows the curve of the great circle on a map, rather
+/// than cutting across it as a single straight line between the endpoints would.
+///
+/// # Arguments
+///
+/// * `a` - A reference to the `Waypoint` at the start of the path.
+/// * `b` - A reference to the `Waypoint` at the end of the path.
+/// * `max_segment_km` - The maximum distance between consecutive points in kilometers.
+///
+/// # Returns
+///
+/// The `(lat, lon)` of each point along the path in order, beginning with `a` and ending
+/// with `b`, spaced evenly apart. If `max_segment_km` isn't positive, or the waypoints are
+/// antipodal (so that no single great circle joins them), only the endpoints are returned.
+///
+/// # Example
+///
+/// ```
+/// use zpath::Waypoint;
+///
+/// let new_york = Waypoint::new(40.7128, -74.0060, String::from("A"));
+/// let london = Waypoint::new(51.5074, -0.1278, String::from("B"));
+///
+/// let points = zpath::densify_segment(&new_york, &london, 500.0);
+/// assert_eq!(points.len(), 13);
+/// assert_eq!(points[0], (new_york.lat, new_york.lon));
+///
+/// // The great circle bows north of both endpoints
+/// assert!(points.iter().any(|&(lat, _)| lat > 52.0));
+/// ```
+pub fn densify_segment(a: &Waypoint, b: &Waypoint, max_segment_km: f32) -> Vec<(Coord, Coord)> {
+    let distance = a.get_distance_to(b);
+    let angle = Coord::from(distance / EARTH_RADIUS);
+
+    let segments = (distance / max_segment_km).ceil();
+    if segments.is_nan() || segments <= 1.0 || angle.sin() < Coord::EPSILON {
+        return vec![(a.lat, a.lon), (b.lat, b.lon)];
     }
-}
+    let segments = segments as usize;
 
-impl Eq for Connection {}
+    let start = to_unit_vector(a.lat, a.lon);
+    let end = to_unit_vector(b.lat, b.lon);
+    let mut points = vec![(a.lat, a.lon)];
 
-impl PartialOrd for Connection {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    // Spherical linear interpolation between the endpoints' unit vectors
+    for step in 1..segments {
+        let fraction = step as Coord / segments as Coord;
+        let start_weight = ((1.0 - fraction) * angle).sin() / angle.sin();
+        let end_weight = (fraction * angle).sin() / angle.sin();
+
+        let point: [Coord; 3] =
+            std::array::from_fn(|axis| start_weight * start[axis] + end_weight * end[axis]);
+        points.push(from_vector(&point));
     }
+
+    points.push((b.lat, b.lon));
+    points
 }
 
-impl Ord for Connection {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.distance
-            .partial_cmp(&other.distance)
-            .unwrap_or(Ordering::Equal)
+/// Derives a seed for the pseudorandom number generators from the current time.
+///
+/// # Returns
+///
+/// A seed which differs from one call to the next.
+fn clock_seed() -> u64 {
+    // A clock set before the epoch still makes for a usable seed
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    since_epoch.as_secs() ^ since_epoch.subsec_nanos() as u64
+}
+
+/// Finds the root of the group containing an index within a union-find forest, where each
+/// index's parent is held in `parents` and roots are their own parent.
+///
+/// # Arguments
+///
+/// * `parents` - The parent of each index, which is updated to shorten paths to the root.
+/// * `index` - The index to find the root of.
+///
+/// # Returns
+///
+/// The index at the root of the group.
+fn find_root(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        // Point each visited node at its grandparent to keep the paths short
+        parents[index] = parents[parents[index]];
+        index = parents[index];
     }
+    index
 }
 
-impl Trie {
-    /// Initializes a new Trie node with no associated waypoint index and an empty set of child nodes.
-    ///
-    /// # Returns
-    ///
-    /// - `Trie`: A new Trie node for geohash indexing.
-    fn new() -> Self {
-        Trie {
-            waypoint_index: None,
-            children: HashMap::new(),
+/// Tests whether a point lies inside a polygon by casting a ray along its line of latitude
+/// and counting the polygon edges crossed, treating latitude and longitude as a flat plane.
+///
+/// # Arguments
+///
+/// * `lat` - The latitude of the point.
+/// * `lon` - The longitude of the point.
+/// * `polygon` - The waypoints at the polygon's vertices, in order.
+///
+/// # Returns
+///
+/// `true` if the point is inside the polygon, `false` otherwise.
+fn point_in_polygon(lat: Coord, lon: Coord, polygon: &[Waypoint]) -> bool {
+    let mut inside = false;
+
+    for i in 0..polygon.len() {
+        let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+
+        if (a.lat > lat) != (b.lat > lat) {
+            let crossing_lon = a.lon + (lat - a.lat) / (b.lat - a.lat) * (b.lon - a.lon);
+            if lon < crossing_lon {
+                inside = !inside;
+            }
         }
     }
 
-    /// Inserts a geohash and the corresponding waypoint index into the Trie. It
-    /// traverses the Trie structure, creating new nodes as needed to represent the geohash.
-    ///
-    /// # Parameters
-    ///
-    /// - `geohash`: A reference to the geohash to insert.
-    /// - `waypoint_index`: The index of the waypoint associated with the geohash.
-    fn insert(&mut self, geohash: &str, waypoint_index: usize) {
-        let mut current_node = self;
+    inside
+}
 
-        for c in geohash.chars() {
-            current_node = current_node.children.entry(c).or_insert(Trie::new());
-        }
+/// Finds the point where two great-circle segments cross, for example to detect where two
+/// routes or connections intersect. Each segment is the shorter arc of the great circle
+/// between its two waypoints.
+///
+/// # Arguments
+///
+/// * `a1`, `a2` - References to the `Waypoint`s at the ends of the first segment.
+/// * `b1`, `b2` - References to the `Waypoint`s at the ends of the second segment.
+///
+/// # Returns
+///
+/// The `(lat, lon)` of the crossing point, or `None` if the segments don't cross. This
+/// includes segments whose great circles intersect beyond the end of either segment, and
+/// segments lying along the same great circle (or of zero length), which have no single
+/// crossing point.
+///
+/// # Example
+///
+/// ```
+/// use zpath::Waypoint;
+///
+/// let west = Waypoint::new(0.0, -10.0, String::from("A"));
+/// let east = Waypoint::new(0.0, 10.0, String::from("B"));
+/// let south = Waypoint::new(-10.0, 5.0, String::from("C"));
+/// let north = Waypoint::new(10.0, 5.0, String::from("D"));
+///
+/// let (lat, lon) = zpath::intersection(&west, &east, &south, &north).unwrap();
+/// assert!(lat.abs() < 1e-3 && (lon - 5.0).abs() < 1e-3);
+///
+/// // The great circles still meet, but beyond the end of the shortened segment
+/// let short_east = Waypoint::new(0.0, 1.0, String::from("E"));
+/// assert_eq!(zpath::intersection(&west, &short_east, &south, &north), None);
+/// ```
+pub fn intersection(
+    a1: &Waypoint,
+    a2: &Waypoint,
+    b1: &Waypoint,
+    b2: &Waypoint,
+) -> Option<(Coord, Coord)> {
+    let (a1, a2) = (
+        to_unit_vector(a1.lat, a1.lon),
+        to_unit_vector(a2.lat, a2.lon),
+    );
+    let (b1, b2) = (
+        to_unit_vector(b1.lat, b1.lon),
+        to_unit_vector(b2.lat, b2.lon),
+    );
+
+    // Each great circle lies in the plane through the Earth's center normal to these vectors;
+    // the circles cross at the two antipodal points along the line where the planes meet
+    let a_normal = cross_product(&a1, &a2);
+    let b_normal = cross_product(&b1, &b2);
+    let line = cross_product(&a_normal, &b_normal);
 
-        current_node.waypoint_index = Some(waypoint_index);
+    let length = dot_product(&line, &line).sqrt();
+    if length < Coord::EPSILON {
+        return None;
     }
 
-    /// Searches the Trie for waypoint indices whose geohash prefixes match the specified
-    /// prefix. It returns a vector of matching waypoint indices; empty if none.
-    ///
-    /// # Parameters
-    ///
-    /// - `prefix`: A reference to the geohash prefix to search for.
-    ///
-    /// # Returns
-    ///
-    /// - `Vec<usize>`: A vector containing waypoint indices matching the geohash prefix.
-    fn get_all_with_prefix(&self, prefix: &str) -> Vec<usize> {
-        let mut current = self;
-        let mut found_waypoints = Vec::new();
+    // A point lies on a segment if it's between both ends, turning the same way as the segment
+    let on_segment =
+        |point: &[Coord; 3], start: &[Coord; 3], end: &[Coord; 3], normal: &[Coord; 3]| {
+            dot_product(&cross_product(start, point), normal) >= 0.0
+                && dot_product(&cross_product(point, end), normal) >= 0.0
+        };
 
-        for c in prefix.chars() {
-            if let Some(child) = current.children.get(&c) {
-                current = child;
-            } else {
-                return Vec::new();
-            }
-        }
+    [1.0, -1.0].into_iter().find_map(|sign: Coord| {
+        let point = line.map(|component| sign * component / length);
 
-        self.collect_waypoints_recursive(&current, &mut found_waypoints);
-        found_waypoints
-    }
+        (on_segment(&point, &a1, &a2, &a_normal) && on_segment(&point, &b1, &b2, &b_normal))
+            .then(|| from_vector(&point))
+    })
+}
 
-    /// Recursively traverses the Trie nodes, collecting waypoint indices from
-    /// nodes that have associated waypoints. It is used internally to implement
-    /// `get_all_with_prefix`.
-    ///
-    /// # Parameters
-    ///
-    /// - `node`: A reference to the Trie node to start collecting from.
-    /// - `waypoints`: A mutable reference to the vector where waypoint indices are collected.
-    fn collect_waypoints_recursive(&self, node: &Trie, waypoints: &mut Vec<usize>) {
-        if let Some(waypoint) = node.waypoint_index {
-            waypoints.push(waypoint);
-        }
+/// Converts a vector in 3D space back into coordinates, as the inverse of `to_unit_vector`.
+/// The vector doesn't need to be of unit length, only non-zero.
+///
+/// # Arguments
+///
+/// * `vector` - The `[x, y, z]` components of the vector.
+///
+/// # Returns
+///
+/// The `(lat, lon)` in degrees of the point where the vector meets the sphere.
+fn from_vector(vector: &[Coord; 3]) -> (Coord, Coord) {
+    let [x, y, z] = *vector;
+    (z.atan2(x.hypot(y)).to_degrees(), y.atan2(x).to_degrees())
+}
 
-        for child in node.children.values() {
-            self.collect_waypoints_recursive(child, waypoints);
-        }
-    }
+/// Calculates the cross product of two vectors in 3D space.
+fn cross_product(a: &[Coord; 3], b: &[Coord; 3]) -> [Coord; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
 }
 
-impl PartialEq for AStarNode {
+/// Calculates the dot product of two vectors in 3D space.
+fn dot_product(a: &[Coord; 3], b: &[Coord; 3]) -> Coord {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Waypoints are identified by their label alone; see `Waypoint::approx_eq` for comparing
+/// their positions.
+impl PartialEq for Waypoint {
     fn eq(&self, other: &Self) -> bool {
-        self.waypoint_index == other.waypoint_index
+        self.label == other.label
     }
 }
 
-impl Eq for AStarNode {}
+impl Eq for Waypoint {}
 
-impl PartialOrd for AStarNode {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Metric for Haversine {
+    fn distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        a.get_distance_to(b)
     }
 }
 
-impl Ord for AStarNode {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other
-            .f_score
-            .partial_cmp(&self.f_score)
-            .unwrap_or(Ordering::Equal)
+impl fmt::Display for ZpathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZpathError::WaypointNotFound(label) => {
+                write!(f, "waypoint '{}' not found in the dataset", label)
+            }
+            ZpathError::InvalidGeohash(geohash) => write!(f, "invalid geohash '{}'", geohash),
+            ZpathError::InvalidPrecision(precision) => write!(
+                f,
+                "invalid precision {} (a geohash must have between 1 and {} characters, \
+                 and a Morton code at most 64 bits)",
+                precision,
+                geohash::MAX_PRECISION
+            ),
+            ZpathError::MalformedRecord(line, reason) => {
+                write!(f, "malformed record on line {}: {}", line, reason)
+            }
+            ZpathError::InvalidCoordinates(lat, lon) => {
+                write!(f, "invalid coordinates ({}, {})", lat, lon)
+            }
+        }
     }
 }
 
-impl Dataset {
-    /// Initializes a new `Dataset` struct with empty waypoint and geohash index containers.
-    /// Can store and manage geospatial data, such as waypoints and their connections.
+impl std::error::Error for ZpathError {}
+
+impl Waypoint {
+    /// Creates a new waypoint at the specified coordinates, computing its geohash from
+    /// them so that the two always agree. The waypoint starts with no connections and no
+    /// metadata.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// - `Dataset`: A new, empty dataset instance.
-    pub fn new() -> Self {
-        Dataset {
-            waypoints: Vec::new(),
-            geohash_index: Trie::new(),
-        }
-    }
-
-    /// Randomly generates waypoints with random latitude and longitude values within the
-    /// specified range and assigns unique labels to each waypoint. It also calculates the
-    /// geohash for each waypoint and inserts it into a geohash index for quick spatial
-    /// lookups.
+    /// * `lat` - The latitude coordinate of the waypoint in degrees.
+    /// * `lon` - The longitude coordinate of the waypoint in degrees.
+    /// * `label` - The label used to identify the waypoint.
     ///
-    /// # Parameters
+    /// # Returns
     ///
-    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// A new `Waypoint` with a geohash of precision 8.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::Waypoint;
+    ///
+    /// let waypoint = Waypoint::new(37.7749, -122.4194, String::from("A"));
+    /// assert_eq!(waypoint.geohash, "9q8yyk8y");
     /// ```
-    pub fn generate_waypoints(&mut self, amt: usize) {
-        let now = SystemTime::now();
-        let since_epoch = now.duration_since(UNIX_EPOCH).unwrap();
-        let seed = since_epoch.as_secs() ^ since_epoch.subsec_nanos() as u64;
-
-        let mut rng = pseudo_random::XorShiftRng::new(seed);
-        // let mut rng = pseudo_random::LcgRng::new(seed);
-
-        let waypoints_length = self.waypoints.len();
-
-        for i in 0..amt {
-            let label = Waypoint::generate_label(waypoints_length + i);
-            let lat = rng.random_f32_in_range(-90.0, 90.0);
-            let lon = rng.random_f32_in_range(-180.0, 180.0);
-            let geohash = geohash::encode(lat, lon, 8);
-
-            let waypoint = Waypoint {
-                label,
-                lat,
-                lon,
-                geohash: geohash.clone(),
-                connections: Vec::new(),
-            };
-
-            self.geohash_index.insert(&geohash, i);
-            self.waypoints.push(waypoint);
-        }
+    pub fn new(lat: Coord, lon: Coord, label: String) -> Self {
+        Self::with_precision(lat, lon, label, GEOHASH_PRECISION)
     }
 
-    /// Creates a new waypoint with the specified latitude and longitude inserts it into the dataset.
+    /// Creates a new waypoint in the same manner as `new`, with a geohash of the given
+    /// precision. Used by datasets configured with a precision other than the default.
     ///
     /// # Arguments
     ///
-    /// * `lat` - The latitude coordinate of the new waypoint in degrees.
-    /// * `lon` - The longitude coordinate of the new waypoint in degrees.
+    /// * `lat` - The latitude coordinate of the waypoint in degrees.
+    /// * `lon` - The longitude coordinate of the waypoint in degrees.
+    /// * `label` - The label used to identify the waypoint.
+    /// * `precision` - The number of characters in the waypoint's geohash.
     ///
-    /// # Example
+    /// # Returns
     ///
-    /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.add_new_waypoint(37.7749, -122.4194);
-    /// ```
-    pub fn add_new_waypoint(&mut self, lat: f32, lon: f32) -> usize {
-        let geohash = geohash::encode(lat, lon, 8);
-        let index = self.waypoints.len();
-
-        let waypoint = Waypoint {
-            label: Waypoint::generate_label(index),
+    /// A new `Waypoint`.
+    fn with_precision(lat: Coord, lon: Coord, label: String, precision: usize) -> Self {
+        Waypoint {
             lat,
             lon,
-            geohash: geohash.clone(),
+            label,
+            geohash: geohash::encode_unchecked(lat, lon, precision),
             connections: Vec::new(),
-        };
-
-        self.geohash_index.insert(&geohash, index);
-        self.waypoints.push(waypoint);
+            metadata: HashMap::new(),
+            active: true,
+        }
+    }
 
-        // If the dataset has already established connections, then assign some
-        // connections to the new waypoint
-        if self.waypoints[0].connections.len() > 0 {
-            let new_connections =
-                self.get_knn_geohash(&self.waypoints[index], self.waypoints[0].connections.len());
-
-            for connection in &new_connections {
-                self.waypoints[connection.waypoint_index]
-                    .connections
-                    .push(Connection {
-                        waypoint_index: index,
-                        distance: connection.distance,
-                    })
-            }
-
-            self.waypoints[index].connections.extend(new_connections);
-        }
-
-        index
+    /// Calculates and returns the great-circle distance between this waypoint
+    /// and a target waypoint in kilometers using the Haversine formula, using
+    /// the Earth's equatorial radius (`EARTH_RADIUS`).
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - A reference to the target `Waypoint` to get the distance to.
+    ///
+    /// # Returns
+    ///
+    /// The great-circle distance in kilometers between this waypoint and the target waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let waypoint1 = Waypoint::new(37.7749, -122.4194, String::from("A"));
+    /// let waypoint2 = Waypoint::new(34.0522, -118.2437, String::from("B"));
+    ///
+    /// let distance_km = waypoint1.get_distance_to(&waypoint2);
+    ///
+    /// println!("Distance between waypoints: {} km", distance_km);
+    /// ```
+    pub fn get_distance_to(&self, target: &Waypoint) -> f32 {
+        self.get_distance_with_radius(target, EARTH_RADIUS)
     }
 
-    /// Searches for a waypoint with a matching label within the dataset and
-    /// returns `Some(index)` if found.
+    /// Calculates and returns the great-circle distance between this waypoint and a
+    /// target waypoint using the Haversine formula, on a sphere of the given radius.
+    /// The result is in the same unit as the radius.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `waypoint`: A reference to the waypoint whose index should be retrieved.
+    /// * `target` - A reference to the target `Waypoint` to get the distance to.
+    /// * `earth_radius` - The radius of the Earth to use, e.g. `EARTH_RADIUS` or `MEAN_EARTH_RADIUS`.
     ///
     /// # Returns
     ///
-    /// - `Option<usize>`: The index of the waypoint within the dataset if found.
-    /// - `None`: If the waypoint isn't found, it returns `None`.
+    /// The great-circle distance between this waypoint and the target waypoint.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
-    /// let waypoint_a = dataset.waypoints[0];
+    /// use zpath::{Dataset, MEAN_EARTH_RADIUS};
     ///
-    /// match dataset.get_waypoint_index(&waypoint_a) {
-    ///     Some(index) => println!("Index of waypoint: {}", index),
-    ///     None => println!("Waypoint not found in the dataset."),
-    /// }
+    /// let mut dataset = Dataset::new();
+    /// let a = dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// let b = dataset.add_new_waypoint(34.0522, -118.2437).unwrap();
+    ///
+    /// let mean_km = dataset.waypoints[a].get_distance_with_radius(&dataset.waypoints[b], MEAN_EARTH_RADIUS);
+    /// assert!(mean_km < dataset.waypoints[a].get_distance_to(&dataset.waypoints[b]));
     /// ```
-    fn get_waypoint_index(&self, waypoint: &Waypoint) -> Option<usize> {
-        self.waypoints
-            .iter()
-            .position(|x| x.label == waypoint.label)
+    pub fn get_distance_with_radius(&self, target: &Waypoint, earth_radius: f32) -> f32 {
+        haversine_distance(self.lat, self.lon, target.lat, target.lon, earth_radius)
     }
 
-    /// Queries the geohash index to retrieve all waypoint indices that share a common
-    /// geohash prefix with the specified geohash. It is used to find waypoints within the same
-    /// geohash cell or adjacent cells.
+    /// Calculates and returns the great-circle distance between this waypoint and a
+    /// target waypoint in the given unit, in the same manner as `get_distance_to`.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `geohash`: A reference to the geohash prefix to search for.
+    /// * `target` - A reference to the target `Waypoint` to get the distance to.
+    /// * `unit` - The unit of distance to return.
     ///
     /// # Returns
     ///
-    /// - `Vec<usize>`: A vector containing the indices of waypoints matching the geohash prefix.
-    /// Empty if none.
+    /// The great-circle distance between this waypoint and the target waypoint in `unit`.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::{Dataset, DistanceUnit};
     ///
-    /// let geohash_prefix = "9t37";
-    /// let matching_indices = dataset.search_geohash(geohash_prefix);
+    /// let mut dataset = Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
     ///
-    /// for index in matching_indices {
-    ///     println!("Matching Waypoint: {:?}", dataset.waypoints[index].label);
-    /// }
+    /// let meters = dataset.waypoints[a].get_distance_to_in(&dataset.waypoints[b], DistanceUnit::M);
+    /// assert!((meters - 111_319.5).abs() < 1.0);
     /// ```
-    fn search_geohash(&self, geohash: &str) -> Vec<usize> {
-        self.geohash_index.get_all_with_prefix(geohash)
+    pub fn get_distance_to_in(&self, target: &Waypoint, unit: DistanceUnit) -> f32 {
+        unit.from_km(self.get_distance_to(target))
     }
 
-    /// Calculates the K-nearest neighbors to a specified waypoint within the dataset
-    /// using a naive approach that iterates through all waypoints in the dataset,
-    /// calculates its distance to all other waypoints in the dataset, sorts them,
-    /// and returns the nearest K.
+    /// Calculates the initial bearing (forward azimuth) from this waypoint to a target
+    /// waypoint, i.e. the direction to set off in to follow the great circle to the target.
+    /// The bearing generally changes along the way, so this is not the bearing on arrival.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `target`: A reference to the waypoint for which K-nearest neighbors are to be found.
-    /// - `k`: The number of nearest neighbors to retrieve.
+    /// * `target` - A reference to the target `Waypoint` to get the bearing to.
     ///
     /// # Returns
     ///
-    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by distance.
+    /// The bearing in degrees clockwise from North, in the range `[0, 360)`.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::Waypoint;
     ///
-    /// let waypoint_a = &dataset.waypoints[0];
-    /// let k = 3;
-    /// let nearest_neighbors = dataset.get_knn_naive(waypoint_a, k);
+    /// let origin = Waypoint::new(0.0, 0.0, String::from("A"));
+    /// let east = Waypoint::new(0.0, 1.0, String::from("B"));
+    /// let south = Waypoint::new(-1.0, 0.0, String::from("C"));
     ///
-    /// for neighbor in nearest_neighbors {
-    ///     println!(
-    ///         "Neighbor: {:?} - {:.2}km",
-    ///         dataset.waypoints[neighbor.waypoint_index].label,
-    ///         neighbor.distance
-    ///     );
-    /// }
+    /// assert!((origin.get_bearing_to(&east) - 90.0).abs() < 1e-3);
+    /// assert!((origin.get_bearing_to(&south) - 180.0).abs() < 1e-3);
     /// ```
-    pub fn get_knn_naive(&self, target: &Waypoint, k: usize) -> Vec<Connection> {
-        let mut nearest_neighbors: Vec<Connection> = Vec::new();
+    pub fn get_bearing_to(&self, target: &Waypoint) -> f32 {
+        let lat1 = self.lat.to_radians();
+        let lat2 = target.lat.to_radians();
+        let dlon = (target.lon - self.lon).to_radians();
 
-        for (i, neighbor) in self.waypoints.iter().enumerate() {
-            if target.label != neighbor.label {
-                nearest_neighbors.push(Connection {
-                    distance: target.get_distance_to(neighbor),
-                    waypoint_index: i,
-                })
-            }
-        }
+        let y = dlon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+        let bearing: Coord = y.atan2(x).to_degrees();
 
-        nearest_neighbors.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
-        nearest_neighbors.truncate(k);
-        nearest_neighbors
+        (bearing as f32 + 360.0) % 360.0
     }
 
-    /// Calculates the K-nearest neighbors to a specified waypoint within the dataset based on
-    /// geohash proximity. Uses a priority queue (binary heap) to efficiently find the nearest
-    /// neighbors. It also considers neighboring geohash cells to handle edge cases.
+    /// Calculates the point reached by travelling a given distance from this waypoint along
+    /// the great circle leaving it at a given initial bearing.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `waypoint`: A reference to the waypoint for which K-nearest neighbors are to be found.
-    /// - `k`: The number of nearest neighbors to retrieve.
+    /// * `bearing` - The initial bearing in degrees clockwise from North.
+    /// * `distance_km` - The distance to travel in kilometers.
     ///
     /// # Returns
     ///
-    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by distance.
+    /// The `(lat, lon)` of the destination, with the longitude in the range `[-180, 180)`.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::Waypoint;
     ///
-    /// let waypoint_a = &dataset.waypoints[0];
-    /// let k = 3;
-    /// let nearest_neighbors = dataset.get_knn_geohash(waypoint_a, k);
+    /// let origin = Waypoint::new(0.0, 179.5, String::from("A"));
     ///
-    /// for neighbor in nearest_neighbors {
-    ///     println!(
-    ///         "Neighbor: {:?} - {:.2}km",
-    ///         dataset.waypoints[neighbor.waypoint_index].label,
-    ///         neighbor.distance
-    ///     );
-    /// }
+    /// // Travelling East across the antimeridian
+    /// let (lat, lon) = origin.destination(90.0, 111.32);
+    /// assert!(lat.abs() < 1e-3 && (lon + 179.5).abs() < 1e-3);
     /// ```
-    pub fn get_knn_geohash(&self, waypoint: &Waypoint, k: usize) -> Vec<Connection> {
-        let mut geohash_to_search = waypoint.geohash.clone();
-        let mut min_heap: BinaryHeap<Connection> = BinaryHeap::new();
-        let mut visited: HashSet<usize> = HashSet::new();
-        visited.insert(self.get_waypoint_index(waypoint).unwrap());
-
-        while min_heap.len() < k {
-            // Remove a level of precision and search the larger geohash cell for neighbors
-            geohash_to_search.pop();
-
-            for neighbor_index in self.search_geohash(&geohash_to_search) {
-                if visited.insert(neighbor_index) {
-                    min_heap.push(Connection {
-                        distance: waypoint.get_distance_to(&self.waypoints[neighbor_index]),
-                        waypoint_index: neighbor_index,
-                    })
-                }
-            }
-        }
+    pub fn destination(&self, bearing: f32, distance_km: f32) -> (Coord, Coord) {
+        let angular_distance = Coord::from(distance_km / EARTH_RADIUS);
+        let bearing = Coord::from(bearing).to_radians();
+        let lat1 = self.lat.to_radians();
+        let lon1 = self.lon.to_radians();
 
-        // k neighbors have been found, but check surrounding cells for edge cases
-        for adjacent_cell in geohash::get_surrounding_cells(&geohash_to_search) {
-            for neighbor_index in self.search_geohash(&adjacent_cell) {
-                if visited.insert(neighbor_index) {
-                    min_heap.push(Connection {
-                        distance: waypoint.get_distance_to(&self.waypoints[neighbor_index]),
-                        waypoint_index: neighbor_index,
-                    })
-                }
-            }
-        }
+        let lat2 = (lat1.sin() * angular_distance.cos()
+            + lat1.cos() * angular_distance.sin() * bearing.cos())
+        .asin();
+        let lon2 = lon1
+            + (bearing.sin() * angular_distance.sin() * lat1.cos())
+                .atan2(angular_distance.cos() - lat1.sin() * lat2.sin());
 
-        // Convert binary heap to vector, truncate to nearest k elements, and return
-        let mut nearest_neighbors = min_heap.into_sorted_vec();
-        nearest_neighbors.truncate(k);
-        nearest_neighbors
+        let lon = (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+        (lat2.to_degrees(), lon)
     }
 
-    /// Iterates through each waypoint in the dataset and assigns connections to it based on
-    /// K-nearest neighbors, calculated using the `get_knn_geohash` method. Populates the
-    /// `connections` field of each waypoint with the calculated connections.
+    /// Calculates the cross-track distance of this waypoint from the great circle passing
+    /// through `a` and `b`; that is, how far this waypoint lies off of the path from `a`
+    /// towards `b`. Along with `along_track_distance`, this can be used to snap a position
+    /// onto the nearest point of a connection.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `amt`: The number of nearest neighbors (K) to consider for each waypoint.
+    /// * `a` - A reference to the `Waypoint` at the start of the path.
+    /// * `b` - A reference to the `Waypoint` at the end of the path.
+    ///
+    /// # Returns
+    ///
+    /// The distance in kilometers between this waypoint and the path. Positive if this
+    /// waypoint lies to the right of the path (when travelling from `a` to `b`), negative if
+    /// it lies to the left.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::Waypoint;
     ///
-    /// let k = 3;
-    /// dataset.assign_connections_geohash(k);
-    /// // All waypoints in dataset will now have 3 connections
+    /// let a = Waypoint::new(0.0, 0.0, String::from("A"));
+    /// let b = Waypoint::new(0.0, 10.0, String::from("B"));
+    /// let north = Waypoint::new(1.0, 5.0, String::from("C"));
+    ///
+    /// // Travelling East, North is to the left
+    /// let offset = north.cross_track_distance(&a, &b);
+    /// assert!(offset < 0.0);
+    /// assert!((offset.abs() - 111.32).abs() < 0.1);
     /// ```
-    pub fn assign_all_connections_geohash(&mut self, amt: usize) {
-        for i in 0..self.waypoints.len() {
-            let connections = self.get_knn_geohash(&self.waypoints[i], amt);
-            self.waypoints[i].connections.extend(connections);
+    pub fn cross_track_distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        let angular_distance = a.get_distance_to(self) / EARTH_RADIUS;
+        let bearing_difference = (a.get_bearing_to(self) - a.get_bearing_to(b)).to_radians();
+
+        (angular_distance.sin() * bearing_difference.sin()).asin() * EARTH_RADIUS
+    }
+
+    /// Calculates the distance from this waypoint to the nearest point on the great-circle
+    /// segment between `a` and `b`, which is the cross-track distance when that point lies
+    /// between them and the distance to the nearer endpoint otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The start of the segment.
+    /// * `b` - The end of the segment.
+    ///
+    /// # Returns
+    ///
+    /// The distance in kilometers, which is never negative.
+    fn distance_to_segment(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        let segment_length = a.get_distance_to(b);
+        if segment_length == 0.0 {
+            return self.get_distance_to(a);
+        }
+
+        let along_track = self.along_track_distance(a, b);
+        if along_track <= 0.0 {
+            self.get_distance_to(a)
+        } else if along_track >= segment_length {
+            self.get_distance_to(b)
+        } else {
+            self.cross_track_distance(a, b).abs()
         }
     }
 
-    /// Iterates through each waypoint in the dataset and assigns connections to it based on
-    /// K-nearest neighbors, calculated using the `get_knn_naive` method. Populates the
-    /// `connections` field of each waypoint with the calculated connections.
+    /// Calculates the along-track distance of this waypoint on the path from `a` towards
+    /// `b`; that is, how far from `a` the closest point on the great circle through `a` and
+    /// `b` lies. See `cross_track_distance` for the distance from that point.
     ///
-    /// # Parameters
+    /// # Arguments
     ///
-    /// - `amt`: The number of nearest neighbors (K) to consider for each waypoint.
+    /// * `a` - A reference to the `Waypoint` at the start of the path.
+    /// * `b` - A reference to the `Waypoint` at the end of the path.
+    ///
+    /// # Returns
+    ///
+    /// The distance in kilometers from `a` to the closest point on the path. Negative if
+    /// that point lies behind `a`, and greater than the length of the path if it lies beyond `b`.
     ///
     /// # Example
     ///
     /// ```
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10);
+    /// use zpath::Waypoint;
     ///
-    /// let k = 3;
-    /// dataset.assign_connections_naive(k);
-    /// // All waypoints in dataset will now have 3 connections
+    /// let a = Waypoint::new(0.0, 0.0, String::from("A"));
+    /// let b = Waypoint::new(0.0, 10.0, String::from("B"));
+    /// let north = Waypoint::new(1.0, 5.0, String::from("C"));
+    /// let behind = Waypoint::new(-1.0, -2.0, String::from("D"));
+    ///
+    /// let along = north.along_track_distance(&a, &b);
+    /// assert!((along - a.get_distance_to(&b) / 2.0).abs() < 0.5);
+    /// assert!(behind.along_track_distance(&a, &b) < 0.0);
     /// ```
-    pub fn assign_all_connections_naive(&mut self, amt: usize) {
-        for i in 0..self.waypoints.len() {
-            let connections = self.get_knn_naive(&self.waypoints[i], amt);
-            self.waypoints[i].connections.extend(connections);
-        }
+    pub fn along_track_distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        let angular_distance = a.get_distance_to(self) / EARTH_RADIUS;
+        let bearing_difference = (a.get_bearing_to(self) - a.get_bearing_to(b)).to_radians();
+        let cross_track = self.cross_track_distance(a, b) / EARTH_RADIUS;
+
+        // Rounding can push the ratio just outside of acos' domain for points on the path
+        let ratio = (angular_distance.cos() / cross_track.cos()).clamp(-1.0, 1.0);
+
+        ratio.acos() * EARTH_RADIUS * bearing_difference.cos().signum()
     }
 
-    /// Calculates the shortest route between a starting waypoint and a goal waypoint
-    /// using the A* (A-star) algorithm.
+    /// Generates sequential labels to act as unique identifiers based on an
+    /// integer value. Will generate labels 'A' through 'Z' first, then 'AA', 'AB',
+    /// etc. through 'ZZ', then 'AAA'... Labels are a bijective base-26 numbering of
+    /// `n + 1`, so every index produces a distinct label of the minimal length.
     ///
     /// # Arguments
     ///
-    /// - `start`: A reference to the starting waypoint.
-    /// - `goal`: A reference to the goal waypoint.
+    /// * `n` - The integer value that represents the waypoint's index in the dataset
     ///
     /// # Returns
     ///
-    /// - `Some(Vec<usize>)`: If a valid route is found, it returns a vector of waypoint indices
-    ///   representing the shortest path from the `start` waypoint to the `goal` waypoint. The
-    ///   vector contains the indices of waypoints in the dataset's 'waypoints' field
-    ///   in the order they should be visited.
-    /// - `None`: If no valid route is found, it returns `None`.
+    /// A string representing the generated label.
     ///
     /// # Example
     ///
     /// ```
-    /// // Create a dataset with 10,000 waypoints, 5 connections each
-    /// let mut dataset = zpath::Dataset::new();
-    /// dataset.generate_waypoints(10000);
-    /// dataset.assign_connections(5);
+    /// use std::collections::HashSet;
+    /// use zpath::Waypoint;
     ///
-    /// let start_waypoint = &dataset.waypoints[0];
-    /// let goal_waypoint = &dataset.waypoints[3];
+    /// assert_eq!(Waypoint::generate_label(0), "A");
+    /// assert_eq!(Waypoint::generate_label(25), "Z");
+    /// assert_eq!(Waypoint::generate_label(27), "AB");
+    /// assert_eq!(Waypoint::generate_label(701), "ZZ");
+    /// assert_eq!(Waypoint::generate_label(702), "AAA");
     ///
-    /// match dataset.get_shortest_route(start_waypoint, goal_waypoint) {
-    ///     Some(route) => {
-    ///        for index in route {
-    ///            print!("{}, ", dataset.waypoints[index].label);
-    ///         }
-    ///     }
-    ///     None => {println!("No valid route found.")}
-    /// }
+    /// let labels: HashSet<String> = (0..1000).map(Waypoint::generate_label).collect();
+    /// assert_eq!(labels.len(), 1000);
     /// ```
-    pub fn get_shortest_route(&self, start: &Waypoint, goal: &Waypoint) -> Option<Vec<usize>> {
-        let mut open_set: BinaryHeap<AStarNode> = BinaryHeap::new();
-        let mut came_from: HashMap<usize, usize> = HashMap::new();
-        let mut g_scores: HashMap<usize, f32> = HashMap::new();
-        let start_index = self.get_waypoint_index(&start).unwrap();
-
-        // Initialize the open set and g_scores map with the starting point
-        g_scores.insert(start_index, 0.0);
-        open_set.push(AStarNode {
-            f_score: 0.0,
-            waypoint_index: start_index,
-        });
-
-        // While there are still routes to explore in the open set...
-        while let Some(node) = open_set.pop() {
-            let current_index = node.waypoint_index;
-            let current_waypoint = &self.waypoints[current_index];
-
-            // If the current waypoint is the goal waypoint...
-            if current_waypoint == goal {
-                let mut path = vec![current_index];
-                let mut current = current_index;
+    pub fn generate_label(n: usize) -> String {
+        let mut result = String::new();
+        let mut remaining = n + 1;
 
-                // Reconstruct the route by following the 'came_from' map and return it
-                while let Some(&previous_index) = came_from.get(&current) {
-                    path.push(previous_index);
-                    current = previous_index;
-                }
-                path.reverse();
-                return Some(path);
-            }
+        while remaining > 0 {
+            let remainder = (remaining - 1) % 26;
+            result.push((b'A' + remainder as u8) as char);
+            remaining = (remaining - 1) / 26;
+        }
 
-            // Explore neighbors of the current waypoint
-            for neighbor in &current_waypoint.connections {
-                let neighbor_index = neighbor.waypoint_index;
-                let g_score = g_scores[&current_index] + neighbor.distance;
+        result.chars().rev().collect()
+    }
 
-                // If the neighbor has not been visited or a shorter path is found...
-                if !g_scores.contains_key(&neighbor_index) || g_score < g_scores[&neighbor_index] {
-                    // This is a better path to the neighbor
-                    came_from.insert(neighbor_index, current_index);
-                    g_scores.insert(neighbor_index, g_score);
+    /// Finds the index a label would have been generated for by `generate_label`, reversing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to reverse.
+    ///
+    /// # Returns
+    ///
+    /// The index `n` for which `generate_label(n)` is `label`, or `None` if no index gives it.
+    fn generated_label_index(label: &str) -> Option<usize> {
+        if label.is_empty() {
+            return None;
+        }
 
-                    // Add the neighbor to the open set for further exploration
-                    let h_score = &self.waypoints[neighbor_index].get_distance_to(goal);
-                    open_set.push(AStarNode {
-                        f_score: g_score + h_score,
-                        waypoint_index: neighbor_index,
-                    });
-                }
+        let mut value: usize = 0;
+        for c in label.bytes() {
+            if !c.is_ascii_uppercase() {
+                return None;
             }
+            value = value
+                .checked_mul(26)?
+                .checked_add((c - b'A') as usize + 1)?;
         }
 
-        None
+        Some(value - 1)
     }
 
-    /// Prints details of a route between waypoints, including the waypoints' labels,
-    /// coordinates, and total route distance.
+    /// Counts the leading characters shared by this waypoint's geohash and another's. The
+    /// longer the shared prefix, the smaller the geohash cell both waypoints fall within,
+    /// which makes it a cheap check for whether they're in roughly the same region.
     ///
     /// # Arguments
     ///
-    /// * `route` - An optional vector of waypoint indices representing the route.
+    /// * `other` - A reference to the other waypoint.
+    ///
+    /// # Returns
+    ///
+    /// The length of the common prefix, from 0 up to the geohash precision of 8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let san_francisco = Waypoint::new(37.7749, -122.4194, String::from("A"));
+    /// let oakland = Waypoint::new(37.8044, -122.2712, String::from("B"));
+    /// let sydney = Waypoint::new(-33.8688, 151.2093, String::from("C"));
+    ///
+    /// assert_eq!(san_francisco.common_geohash_prefix_len(&oakland), 2);
+    /// assert_eq!(san_francisco.common_geohash_prefix_len(&sydney), 0);
+    /// ```
+    pub fn common_geohash_prefix_len(&self, other: &Waypoint) -> usize {
+        self.geohash
+            .chars()
+            .zip(other.geohash.chars())
+            .take_while(|(a, b)| a == b)
+            .count()
+    }
+
+    /// Checks whether this waypoint is at the same position as another, to within a
+    /// tolerance, regardless of their labels. Unlike `==`, which compares labels, this is
+    /// suited to finding duplicate points.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - A reference to the `Waypoint` to compare with.
+    /// * `tol_km` - The greatest distance in kilometers at which the waypoints are considered
+    ///   to be at the same position (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// `true` if the great-circle distance between the waypoints is within `tol_km`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let a = Waypoint::new(37.7749, -122.4194, String::from("A"));
+    /// let b = Waypoint::new(37.7750, -122.4194, String::from("B"));
+    /// let also_a = Waypoint::new(0.0, 0.0, String::from("A"));
+    ///
+    /// assert!(a.approx_eq(&b, 0.05));
+    /// assert!(a != b);
+    /// assert!(!a.approx_eq(&also_a, 0.05));
+    /// assert!(a == also_a);
+    /// ```
+    pub fn approx_eq(&self, other: &Waypoint, tol_km: f32) -> bool {
+        self.get_distance_to(other) <= tol_km
+    }
+
+    /// Renders the waypoint as a `POINT` in the Well-Known Text (WKT) format, for loading
+    /// into spatial databases such as PostGIS. Following OGC conventions, the longitude is
+    /// written before the latitude.
+    ///
+    /// # Returns
+    ///
+    /// A string of the form `POINT(lon lat)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let waypoint = Waypoint::new(37.5, -122.25, String::from("A"));
+    /// assert_eq!(waypoint.to_wkt(), "POINT(-122.25 37.5)");
+    /// ```
+    pub fn to_wkt(&self) -> String {
+        format!("POINT({} {})", self.lon, self.lat)
+    }
+
+    /// Converts the latitude and longitude coordinates of a waypoint into a
+    /// more readable Degree-Minute-Second (DMS) format string, with the seconds
+    /// given to two decimal places.
+    ///
+    /// # Returns
+    ///
+    /// A string representing the coordinates in DMS format.
+    /// For example, "37°45'30.00\"N, 122°25'12.00\"W".
+    pub fn get_dms(&self) -> String {
+        self.get_dms_precision(2)
+    }
+
+    /// Converts the coordinates of a waypoint into a Degree-Minute-Second (DMS) format
+    /// string in the same manner as `get_dms`, with the seconds given to a chosen number of
+    /// decimal places. Seconds that round up to 60 are carried into the minutes (and minutes
+    /// into the degrees), so 60 seconds or 60 minutes are never displayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `decimals` - The number of decimal places to give the seconds to, at most 9.
+    ///
+    /// # Returns
+    ///
+    /// A string representing the coordinates in DMS format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let waypoint = Waypoint::new(37.7583, -122.42, String::from("A"));
+    /// assert_eq!(waypoint.get_dms_precision(0), "37°45'30\"N, 122°25'12\"W");
+    ///
+    /// // Just short of 38 degrees, the seconds round up to a whole degree
+    /// let waypoint = Waypoint::new(37.999999, -122.0, String::from("B"));
+    /// assert_eq!(waypoint.get_dms(), "38°0'0.00\"N, 122°0'0.00\"W");
+    /// ```
+    pub fn get_dms_precision(&self, decimals: usize) -> String {
+        let lat_direction = if self.lat >= 0.0 { 'N' } else { 'S' }; // Assign the cardinal direction based on sign
+        let long_direction = if self.lon >= 0.0 { 'E' } else { 'W' };
+
+        format!(
+            "{}{}, {}{}",
+            Waypoint::format_dms(self.lat, decimals),
+            lat_direction,
+            Waypoint::format_dms(self.lon, decimals),
+            long_direction
+        )
+    }
+
+    /// Formats the coordinates of a waypoint as signed decimal degrees, latitude first.
+    ///
+    /// # Arguments
+    ///
+    /// * `decimals` - The number of decimal places to give each coordinate to.
+    ///
+    /// # Returns
+    ///
+    /// A string of the form `lat, lon`, e.g. "37.774900, -122.419400".
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::Waypoint;
+    ///
+    /// let waypoint = Waypoint::new(37.5, -122.25, String::from("A"));
+    /// assert_eq!(waypoint.get_decimal_degrees(6), "37.500000, -122.250000");
+    /// assert_eq!(waypoint.get_decimal_degrees(1), "37.5, -122.2");
+    /// ```
+    pub fn get_decimal_degrees(&self, decimals: usize) -> String {
+        format!("{:.*}, {:.*}", decimals, self.lat, decimals, self.lon)
+    }
+
+    /// Formats the magnitude of a coordinate as degrees, minutes and seconds. The coordinate
+    /// is rounded to a whole number of the smallest displayed fraction of a second before
+    /// being split up, so that rounding carries into the minutes and degrees.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The coordinate in degrees. Its sign is ignored.
+    /// * `decimals` - The number of decimal places to give the seconds to, at most 9.
+    ///
+    /// # Returns
+    ///
+    /// A string of the form `37°45'30.00"`, without a cardinal direction.
+    fn format_dms(value: Coord, decimals: usize) -> String {
+        let decimals = decimals.min(9) as u32;
+        let scale = 10u64.pow(decimals);
+
+        // Count in units of the last displayed decimal place of a second
+        let total = (value.abs() * 3600.0 * scale as Coord).round() as u64;
+        let degrees = total / (3600 * scale);
+        let minutes = total / (60 * scale) % 60;
+        let seconds = total % (60 * scale);
+
+        if decimals == 0 {
+            format!("{}°{}'{}\"", degrees, minutes, seconds)
+        } else {
+            format!(
+                "{}°{}'{}.{:0width$}\"",
+                degrees,
+                minutes,
+                seconds / scale,
+                seconds % scale,
+                width = decimals as usize
+            )
+        }
+    }
+}
+
+impl LabelScheme {
+    /// Generates the label for the waypoint at a given index under this scheme.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The index of the waypoint in the dataset.
+    ///
+    /// # Returns
+    ///
+    /// The label for the waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::LabelScheme;
+    ///
+    /// assert_eq!(LabelScheme::Alpha.label(27), "AB");
+    /// assert_eq!(LabelScheme::Numeric { width: 4 }.label(42), "0042");
+    /// assert_eq!(LabelScheme::Numeric { width: 2 }.label(1234), "1234");
+    /// assert_eq!(LabelScheme::Prefixed(String::from("WP-")).label(42), "WP-0042");
+    /// ```
+    pub fn label(&self, n: usize) -> String {
+        match self {
+            LabelScheme::Alpha => Waypoint::generate_label(n),
+            LabelScheme::Numeric { width } => format!("{:0width$}", n, width = width),
+            LabelScheme::Prefixed(prefix) => format!("{}{:04}", prefix, n),
+        }
+    }
+}
+
+impl DistanceUnit {
+    /// Converts a distance in kilometers into this unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `km` - The distance in kilometers.
+    ///
+    /// # Returns
+    ///
+    /// The distance expressed in this unit.
+    pub fn from_km(self, km: f32) -> f32 {
+        match self {
+            DistanceUnit::Km => km,
+            DistanceUnit::Mi => km / 1.609344,
+            DistanceUnit::M => km * 1000.0,
+            DistanceUnit::NauticalMi => km / 1.852,
+        }
+    }
+}
+
+impl Connection {
+    /// Creates a new connection to the waypoint at the given index, weighted by its distance.
+    ///
+    /// # Arguments
+    ///
+    /// * `waypoint_index` - The index of the connected waypoint in the dataset.
+    /// * `distance` - The distance to the connected waypoint in kilometers.
+    ///
+    /// # Returns
+    ///
+    /// A new `Connection` whose `weight` is equal to its `distance`, with no speed.
+    pub fn new(waypoint_index: usize, distance: f32) -> Self {
+        Connection {
+            distance,
+            weight: distance,
+            waypoint_index,
+            speed_kph: None,
+        }
+    }
+}
+
+impl PartialEq for Connection {
+    fn eq(&self, other: &Self) -> bool {
+        self.waypoint_index == other.waypoint_index
+    }
+}
+
+impl Eq for Connection {}
+
+impl PartialOrd for Connection {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Connection {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl Trie {
+    /// Initializes a new Trie node with no associated waypoint index and an empty set of child nodes.
+    ///
+    /// # Returns
+    ///
+    /// - `Trie`: A new Trie node for geohash indexing.
+    fn new() -> Self {
+        Trie {
+            label: String::new(),
+            waypoint_indices: Vec::new(),
+            children: HashMap::new(),
+        }
+    }
+
+    /// Inserts a geohash and the corresponding waypoint index into the Trie. It
+    /// traverses the Trie structure, creating new nodes as needed to represent the geohash.
+    /// When the geohash diverges partway along an existing edge, that edge is split in two
+    /// at the point of divergence.
+    ///
+    /// # Parameters
+    ///
+    /// - `geohash`: A reference to the geohash to insert.
+    /// - `waypoint_index`: The index of the waypoint associated with the geohash.
+    fn insert(&mut self, geohash: &str, waypoint_index: usize) {
+        let mut current_node = self;
+        let mut remaining = geohash;
+
+        while let Some(c) = remaining.chars().next() {
+            let child = match current_node.children.entry(c) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                // No edge begins with the next character, so the rest of the geohash becomes a new leaf
+                Entry::Vacant(entry) => {
+                    entry.insert(Trie {
+                        label: String::from(remaining),
+                        waypoint_indices: vec![waypoint_index],
+                        children: HashMap::new(),
+                    });
+                    return;
+                }
+            };
+            let common_length = child
+                .label
+                .chars()
+                .zip(remaining.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            // The geohash diverges partway along the edge; split the edge at that point,
+            // moving the child's contents down into a new node beneath it
+            if common_length < child.label.len() {
+                let suffix = child.label.split_off(common_length);
+                let lower_node = Trie {
+                    waypoint_indices: std::mem::take(&mut child.waypoint_indices),
+                    children: std::mem::take(&mut child.children),
+                    label: suffix,
+                };
+
+                child
+                    .children
+                    .insert(lower_node.label.chars().next().unwrap(), lower_node);
+            }
+
+            current_node = child;
+            remaining = &remaining[common_length..];
+        }
+
+        current_node.waypoint_indices.push(waypoint_index);
+    }
+
+    /// Searches the Trie for waypoint indices whose geohash prefixes match the specified
+    /// prefix. It returns a vector of matching waypoint indices; empty if none. The prefix
+    /// may end partway along an edge, in which case everything beneath that edge matches.
+    ///
+    /// # Parameters
+    ///
+    /// - `prefix`: A reference to the geohash prefix to search for.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: A vector containing waypoint indices matching the geohash prefix.
+    fn get_all_with_prefix(&self, prefix: &str) -> Vec<usize> {
+        let mut found_waypoints = Vec::new();
+
+        if let Some(node) = self.find_prefix(prefix) {
+            self.collect_waypoints_recursive(node, &mut found_waypoints, usize::MAX);
+        }
+
+        found_waypoints
+    }
+
+    /// Searches the Trie for waypoint indices whose geohash prefixes match the specified
+    /// prefix in the same manner as `get_all_with_prefix`, but stops once `max` matches have
+    /// been found. Useful for previewing the contents of a large cell without collecting
+    /// every waypoint inside it.
+    ///
+    /// # Parameters
+    ///
+    /// - `prefix`: A reference to the geohash prefix to search for.
+    /// - `max`: The maximum number of waypoint indices to return.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: A vector containing up to `max` waypoint indices matching the geohash
+    ///   prefix, in no particular order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(1000);
+    ///
+    /// let sample = dataset.geohash_index.get_all_with_prefix_limited("", 10);
+    /// assert_eq!(sample.len(), 10);
+    ///
+    /// let all = dataset.geohash_index.get_all_with_prefix_limited("", 5000);
+    /// assert_eq!(all.len(), 1000);
+    /// ```
+    pub fn get_all_with_prefix_limited(&self, prefix: &str, max: usize) -> Vec<usize> {
+        let mut found_waypoints = Vec::new();
+
+        if let Some(node) = self.find_prefix(prefix) {
+            self.collect_waypoints_recursive(node, &mut found_waypoints, max);
+        }
+
+        found_waypoints
+    }
+
+    /// Walks down the Trie along the specified prefix and returns the node beneath which
+    /// every geohash matches the prefix. The prefix may end partway along an edge, in which
+    /// case the node at the end of that edge is returned.
+    ///
+    /// # Parameters
+    ///
+    /// - `prefix`: A reference to the geohash prefix to search for.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<&Trie>`: The node matching the prefix, or `None` if no geohash matches.
+    fn find_prefix(&self, prefix: &str) -> Option<&Trie> {
+        let mut current = self;
+        let mut remaining = prefix;
+
+        while let Some(c) = remaining.chars().next() {
+            let child = current.children.get(&c)?;
+
+            if let Some(rest) = remaining.strip_prefix(child.label.as_str()) {
+                remaining = rest;
+            } else if child.label.starts_with(remaining) {
+                remaining = "";
+            } else {
+                return None;
+            }
+
+            current = child;
+        }
+
+        Some(current)
+    }
+
+    /// Creates a lazy iterator over the waypoint indices held by this node and all of
+    /// its descendants.
+    ///
+    /// # Returns
+    ///
+    /// - `TrieIter`: An iterator yielding waypoint indices.
+    fn iter(&self) -> TrieIter<'_> {
+        TrieIter {
+            stack: vec![self],
+            pending: [].iter(),
+        }
+    }
+
+    /// Recursively traverses the Trie nodes, collecting waypoint indices from
+    /// nodes that have associated waypoints. It is used internally to implement
+    /// `get_all_with_prefix` and `get_all_with_prefix_limited`.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: A reference to the Trie node to start collecting from.
+    /// - `waypoints`: A mutable reference to the vector where waypoint indices are collected.
+    /// - `limit`: The number of waypoint indices at which to stop collecting.
+    fn collect_waypoints_recursive(&self, node: &Trie, waypoints: &mut Vec<usize>, limit: usize) {
+        if waypoints.len() >= limit {
+            return;
+        }
+
+        let remaining = limit - waypoints.len();
+        waypoints.extend(node.waypoint_indices.iter().take(remaining));
+
+        for child in node.children.values() {
+            if waypoints.len() >= limit {
+                break;
+            }
+            self.collect_waypoints_recursive(child, waypoints, limit);
+        }
+    }
+
+    /// Counts the nodes in the Trie, including this node and all of its descendants.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The total number of nodes.
+    fn count_nodes(&self) -> usize {
+        1 + self
+            .children
+            .values()
+            .map(|child| child.count_nodes())
+            .sum::<usize>()
+    }
+
+    /// Finds the length of the longest chain of nodes below this node. As single-child
+    /// chains are compressed, this may be less than the length of the longest geohash.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The depth of the deepest descendant; `0` if this node has no children.
+    fn max_depth(&self) -> usize {
+        self.children
+            .values()
+            .map(|child| 1 + child.max_depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Recursively counts how many waypoints are held by each node that holds any, and
+    /// tallies the nodes by that count. Used to implement `Dataset::index_histogram`.
+    ///
+    /// # Parameters
+    ///
+    /// - `histogram`: A mutable reference to the map from number of waypoints held to number
+    ///   of nodes holding that many.
+    fn tally_occupancy(&self, histogram: &mut HashMap<usize, usize>) {
+        if !self.waypoint_indices.is_empty() {
+            *histogram.entry(self.waypoint_indices.len()).or_insert(0) += 1;
+        }
+
+        for child in self.children.values() {
+            child.tally_occupancy(histogram);
+        }
+    }
+
+    /// Recursively writes this node and its descendants in a compact binary form: the
+    /// length and bytes of the node's edge label, the number and values of its waypoint
+    /// indices, then the number of children followed by each child. Numbers are written
+    /// in little-endian order. Used to implement `Dataset::save_index`.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: A mutable reference to the writer to write to.
+    ///
+    /// # Returns
+    ///
+    /// - `io::Result<()>`: Any error from the writer, or `io::ErrorKind::InvalidData` if a
+    ///   waypoint index doesn't fit in 32 bits.
+    fn write_to(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writer.write_all(&[self.label.len() as u8])?;
+        writer.write_all(self.label.as_bytes())?;
+
+        writer.write_all(&(self.waypoint_indices.len() as u32).to_le_bytes())?;
+        for &index in &self.waypoint_indices {
+            let index = u32::try_from(index).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "waypoint index exceeds 32 bits")
+            })?;
+            writer.write_all(&index.to_le_bytes())?;
+        }
+
+        writer.write_all(&[self.children.len() as u8])?;
+        for child in self.children.values() {
+            child.write_to(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Recursively reads a node and its descendants in the form written by `write_to`,
+    /// checking that every waypoint index refers to an existing waypoint, that edge labels
+    /// only hold geohash characters, and that no path from the root is longer than a
+    /// geohash can be. Every node below the root must have a non-empty label beginning with
+    /// a character none of its siblings begin with, which is checked before anything below
+    /// it is read. Each level of recursion therefore lengthens the path, and bounding the
+    /// length of each path bounds the depth of recursion, so crafted input can't overflow
+    /// the stack. Used to implement `Dataset::load_index`.
+    ///
+    /// # Parameters
+    ///
+    /// - `reader`: A mutable reference to the reader to read from.
+    /// - `waypoint_count`: The number of waypoints the index may refer to.
+    /// - `depth`: The total length of the edge labels from the root to this node's parent.
+    /// - `siblings`: The children already read from this node's parent, or `None` for the
+    ///   root.
+    ///
+    /// # Returns
+    ///
+    /// - `io::Result<Trie>`: The node read, any error from the reader, or
+    ///   `io::ErrorKind::InvalidData` if the data isn't a valid index for the waypoints.
+    fn read_from(
+        reader: &mut impl io::Read,
+        waypoint_count: usize,
+        depth: usize,
+        siblings: Option<&HashMap<char, Trie>>,
+    ) -> io::Result<Trie> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+
+        let mut byte = [0; 1];
+        let mut word = [0; 4];
+
+        reader.read_exact(&mut byte)?;
+        let depth = depth + byte[0] as usize;
+        if depth > geohash::MAX_PRECISION {
+            return Err(invalid("path is longer than any geohash"));
+        }
+
+        let mut label = vec![0; byte[0] as usize];
+        reader.read_exact(&mut label)?;
+        let label = String::from_utf8(label).map_err(|_| invalid("edge label isn't UTF-8"))?;
+        geohash::validate(&label).map_err(|_| invalid("edge label isn't a geohash"))?;
+
+        if let Some(siblings) = siblings {
+            let first_char = label
+                .chars()
+                .next()
+                .ok_or_else(|| invalid("child node has an empty edge label"))?;
+            if siblings.contains_key(&first_char) {
+                return Err(invalid("two child nodes begin with the same character"));
+            }
+        }
+
+        reader.read_exact(&mut word)?;
+        let index_count = u32::from_le_bytes(word) as usize;
+        if index_count > waypoint_count {
+            return Err(invalid("node holds more indices than there are waypoints"));
+        }
+
+        let mut waypoint_indices = Vec::with_capacity(index_count);
+        for _ in 0..index_count {
+            reader.read_exact(&mut word)?;
+            let index = u32::from_le_bytes(word) as usize;
+            if index >= waypoint_count {
+                return Err(invalid("waypoint index out of range"));
+            }
+            waypoint_indices.push(index);
+        }
+
+        reader.read_exact(&mut byte)?;
+        let mut children = HashMap::with_capacity(byte[0] as usize);
+        for _ in 0..byte[0] {
+            let child = Trie::read_from(reader, waypoint_count, depth, Some(&children))?;
+            // The child's label has been checked to be non-empty and unique among its siblings
+            let first_char = child.label.chars().next().unwrap();
+            children.insert(first_char, child);
+        }
+
+        Ok(Trie {
+            label,
+            children,
+            waypoint_indices,
+        })
+    }
+}
+
+impl<'a> Iterator for TrieIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some(&waypoint_index) = self.pending.next() {
+                return Some(waypoint_index);
+            }
+
+            let node = self.stack.pop()?;
+            self.stack.extend(node.children.values());
+            self.pending = node.waypoint_indices.iter();
+        }
+    }
+}
+
+impl AStarSearch {
+    /// Reconstructs the route to a visited waypoint by following the `came_from` map back
+    /// to the start.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of a waypoint visited by the search.
+    ///
+    /// # Returns
+    ///
+    /// - `(Vec<usize>, f32)`: The waypoint indices of the route from the start, and its cost.
+    fn route_to(&self, index: usize) -> (Vec<usize>, f32) {
+        let mut route = vec![index];
+        let mut current = index;
+        while let Some(&previous_index) = self.came_from.get(&current) {
+            route.push(previous_index);
+            current = previous_index;
+        }
+        route.reverse();
+
+        (route, self.g_scores[&index])
+    }
+}
+
+impl PartialEq for AStarNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for AStarNode {}
+
+impl PartialOrd for AStarNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AStarNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .f_score
+            .partial_cmp(&self.f_score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| {
+                self.g_score
+                    .partial_cmp(&other.g_score)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| other.waypoint_index.cmp(&self.waypoint_index))
+    }
+}
+
+impl Default for Dataset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dataset {
+    /// Initializes a new `Dataset` struct with empty waypoint and geohash index containers.
+    /// Can store and manage geospatial data, such as waypoints and their connections.
+    ///
+    /// # Returns
+    ///
+    /// - `Dataset`: A new, empty dataset instance.
+    pub fn new() -> Self {
+        Dataset {
+            waypoints: Vec::new(),
+            geohash_index: Trie::new(),
+            directed: true,
+            metric: None,
+            earth_radius: EARTH_RADIUS,
+            precision: GEOHASH_PRECISION,
+            requested_connections: 0,
+        }
+    }
+
+    /// Creates a new dataset from newline-delimited JSON, with one waypoint per line in the
+    /// form `{"label": "A", "lat": 37.7749, "lon": -122.4194}`. Lines are read and inserted
+    /// into the geohash index one at a time, so the whole input is never held in memory.
+    /// Blank lines are ignored, and any other line that isn't a valid record is skipped
+    /// and reported rather than aborting the import. A record without a `label` is given
+    /// a generated label, and any other fields are kept in the waypoint's `metadata`.
+    ///
+    /// # Parameters
+    ///
+    /// - `reader`: The source of the JSON lines, e.g. a `BufReader` over a file.
+    ///
+    /// # Returns
+    ///
+    /// - `std::io::Result<(Dataset, Vec<ZpathError>)>`: The new dataset and a
+    ///   `ZpathError::MalformedRecord` for each skipped line, or the error that stopped the
+    ///   input from being read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let input = "{\"label\": \"SF\", \"lat\": 37.7749, \"lon\": -122.4194, \"category\": \"city\"}\n\
+    ///              not json\n\
+    ///              {\"label\": \"LA\", \"lat\": 34.0522, \"lon\": -118.2437}\n\
+    ///              {\"label\": \"Nowhere\", \"lat\": 95.0, \"lon\": 0.0}\n";
+    ///
+    /// let (dataset, errors) = zpath::Dataset::from_jsonl(std::io::Cursor::new(input)).unwrap();
+    ///
+    /// assert_eq!(dataset.waypoints.len(), 2);
+    /// assert_eq!(dataset.waypoints[1].label, "LA");
+    /// assert_eq!(dataset.waypoints[0].metadata["category"], "city");
+    /// assert_eq!(dataset.get_knn_geohash_by_index(0, 1)[0].waypoint_index, 1);
+    ///
+    /// let lines: Vec<usize> = errors
+    ///     .iter()
+    ///     .map(|error| match error {
+    ///         zpath::ZpathError::MalformedRecord(line, _) => *line,
+    ///         _ => unreachable!(),
+    ///     })
+    ///     .collect();
+    /// assert_eq!(lines, vec![2, 4]);
+    /// ```
+    pub fn from_jsonl(
+        mut reader: impl std::io::BufRead,
+    ) -> std::io::Result<(Dataset, Vec<ZpathError>)> {
+        let mut dataset = Dataset::new();
+        let mut errors = Vec::new();
+        let mut line = String::new();
+        let mut line_number = 0;
+
+        while {
+            line.clear();
+            reader.read_line(&mut line)? > 0
+        } {
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let index = dataset.waypoints.len();
+            match Self::parse_jsonl_record(&line, index) {
+                Ok(waypoint) => {
+                    dataset.geohash_index.insert(&waypoint.geohash, index);
+                    dataset.waypoints.push(waypoint);
+                }
+                Err(reason) => errors.push(ZpathError::MalformedRecord(line_number, reason)),
+            }
+        }
+
+        Ok((dataset, errors))
+    }
+
+    /// Parses a single line of newline-delimited JSON into a waypoint.
+    ///
+    /// # Parameters
+    ///
+    /// - `line`: The JSON object to parse.
+    /// - `index`: The index the waypoint will have, used to generate a label if it has none.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Waypoint, String>`: The parsed waypoint, or the reason the line is invalid.
+    fn parse_jsonl_record(line: &str, index: usize) -> Result<Waypoint, String> {
+        let mut record = json::parse_flat_object(line)?;
+
+        let mut coordinate = |key: &str, limit: f64| match record.remove(key) {
+            Some(json::JsonValue::Number(value)) if value.abs() <= limit => Ok(value as Coord),
+            Some(json::JsonValue::Number(value)) => {
+                Err(format!("'{}' {} is out of range", key, value))
+            }
+            Some(_) => Err(format!("'{}' is not a number", key)),
+            None => Err(format!("missing '{}'", key)),
+        };
+        let lat = coordinate("lat", 90.0)?;
+        let lon = coordinate("lon", 180.0)?;
+
+        let label = match record.remove("label") {
+            Some(json::JsonValue::String(label)) => label,
+            None | Some(json::JsonValue::Null) => Waypoint::generate_label(index),
+            Some(_) => return Err(String::from("'label' is not a string")),
+        };
+
+        let mut waypoint = Waypoint::new(lat, lon, label);
+        for (key, value) in record {
+            let value = match value {
+                json::JsonValue::String(value) => value,
+                json::JsonValue::Number(value) => value.to_string(),
+                json::JsonValue::Bool(value) => value.to_string(),
+                json::JsonValue::Null => continue,
+            };
+            waypoint.metadata.insert(key, value);
+        }
+
+        Ok(waypoint)
+    }
+
+    /// Creates a new dataset from a configuration, generating its waypoints and then
+    /// assigning their connections with `assign_all_connections_geohash`.
+    ///
+    /// # Parameters
+    ///
+    /// - `config`: The settings to build the dataset with.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Dataset, ZpathError>`: The new dataset, or `ZpathError::InvalidPrecision` if
+    ///   the configured geohash precision is zero or greater than `geohash::MAX_PRECISION`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let config = zpath::DatasetConfig::new().waypoints(100).seed(7).connections(4);
+    ///
+    /// let first = zpath::Dataset::build(config.clone()).unwrap();
+    /// let second = zpath::Dataset::build(config).unwrap();
+    /// assert_eq!(first.waypoints[0].geohash, second.waypoints[0].geohash);
+    /// assert_eq!(first.connection_stats().connection_count, 400);
+    ///
+    /// let too_precise = zpath::DatasetConfig::new().precision(40);
+    /// assert!(zpath::Dataset::build(too_precise).is_err());
+    /// ```
+    pub fn build(config: DatasetConfig) -> Result<Self, ZpathError> {
+        if config.precision == 0 || config.precision > geohash::MAX_PRECISION {
+            return Err(ZpathError::InvalidPrecision(config.precision));
+        }
+
+        let mut dataset = Dataset::new();
+        dataset.precision = config.precision;
+        dataset.directed = !config.symmetric;
+
+        let seed = config.seed.unwrap_or_else(clock_seed);
+        dataset.generate_waypoints_with(config.waypoints, config.rng, seed);
+
+        if config.connections > 0 {
+            dataset.assign_all_connections_geohash(config.connections);
+        }
+
+        Ok(dataset)
+    }
+
+    /// Sets the metric used to measure distances between waypoints for nearest neighbor
+    /// searches and the routing heuristic. Connections that have already been assigned keep the
+    /// distances they were assigned with, so the metric should be set before assigning
+    /// connections. Routing relies on the metric never overestimating the distance between
+    /// waypoints; in debug builds, routing panics if it finds a connection whose distance is
+    /// shorter than the metric's distance across it, rather than silently returning a route
+    /// which may not be the shortest. Connections whose `weight` (or cost, when routing with a
+    /// custom cost function) is lower than their distance are fine. The metric must be `Send`
+    /// and `Sync`, so that datasets can still be shared between threads.
+    ///
+    /// # Parameters
+    ///
+    /// - `metric`: The metric to use.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::{Metric, Waypoint};
+    ///
+    /// // Treat coordinates as positions on a flat grid
+    /// struct Manhattan;
+    ///
+    /// impl Metric for Manhattan {
+    ///     fn distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+    ///         ((a.lat - b.lat).abs() + (a.lon - b.lon).abs()) as f32
+    ///     }
+    /// }
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// let origin = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(3.0, 3.0).unwrap();
+    /// let east = dataset.add_new_waypoint(0.0, 5.0).unwrap();
+    ///
+    /// dataset.set_metric(Manhattan);
+    /// let nearest = dataset.get_knn_naive(&dataset.waypoints[origin], 1, Some(origin));
+    /// assert_eq!(nearest[0].waypoint_index, east);
+    /// assert_eq!(nearest[0].distance, 5.0);
+    ///
+    /// // Datasets can be shared between threads whatever their metric
+    /// fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    /// assert_send_sync(&dataset);
+    /// ```
+    ///
+    /// Changing to a metric which measures longer distances after assigning connections
+    /// makes the routing heuristic inadmissible:
+    ///
+    /// ```should_panic
+    /// use zpath::{Metric, Waypoint};
+    ///
+    /// struct Doubled;
+    ///
+    /// impl Metric for Doubled {
+    ///     fn distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+    ///         2.0 * a.get_distance_to(b)
+    ///     }
+    /// }
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let goal = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// dataset.set_metric(Doubled);
+    /// dataset.get_shortest_route_by_index(start, goal);
+    /// ```
+    pub fn set_metric(&mut self, metric: impl Metric + Send + Sync + 'static) {
+        self.metric = Some(Box::new(metric));
+    }
+
+    /// Sets the radius of the sphere on which the dataset measures great-circle distances,
+    /// e.g. `MEAN_EARTH_RADIUS` for more accurate distances on average than the default of
+    /// `EARTH_RADIUS`. The radius is used by the default metric, and by every method of the
+    /// dataset which measures distances in kilometers. As with `set_metric`, connections
+    /// that have already been assigned keep the distances they were assigned with, so the
+    /// radius should be set before assigning connections.
+    ///
+    /// `Waypoint` methods such as `Waypoint::get_distance_to` have no dataset to refer to
+    /// and always use `EARTH_RADIUS`; see `Waypoint::get_distance_with_radius`. So does the
+    /// `Haversine` metric, if it's set explicitly.
+    ///
+    /// # Parameters
+    ///
+    /// - `earth_radius`: The radius of the Earth in kilometers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::{EARTH_RADIUS, MEAN_EARTH_RADIUS};
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let equatorial_km = dataset.get_knn_naive_by_index(a, 1)[0].distance;
+    ///
+    /// dataset.set_earth_radius(MEAN_EARTH_RADIUS);
+    /// assert_eq!(dataset.earth_radius(), MEAN_EARTH_RADIUS);
+    /// let mean_km = dataset.get_knn_naive_by_index(a, 1)[0].distance;
+    /// assert!((mean_km / equatorial_km - MEAN_EARTH_RADIUS / EARTH_RADIUS).abs() < 1e-6);
+    /// ```
+    pub fn set_earth_radius(&mut self, earth_radius: f32) {
+        self.earth_radius = earth_radius;
+    }
+
+    /// Returns the radius of the sphere on which the dataset measures great-circle distances
+    /// (see `set_earth_radius`).
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The radius of the Earth in kilometers.
+    pub fn earth_radius(&self) -> f32 {
+        self.earth_radius
+    }
+
+    /// Measures the distance between two waypoints with the dataset's metric, which is the
+    /// great-circle distance on the dataset's Earth radius unless set with `set_metric`.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: A reference to the first waypoint.
+    /// - `b`: A reference to the second waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The distance between the waypoints.
+    fn metric_distance(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        match &self.metric {
+            Some(metric) => metric.distance(a, b),
+            None => self.distance_km(a, b),
+        }
+    }
+
+    /// Calculates the great-circle distance between two waypoints on the dataset's Earth
+    /// radius, regardless of the dataset's metric.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: A reference to the first waypoint.
+    /// - `b`: A reference to the second waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The distance between the waypoints in kilometers.
+    fn distance_km(&self, a: &Waypoint, b: &Waypoint) -> f32 {
+        a.get_distance_with_radius(b, self.earth_radius)
+    }
+
+    /// Randomly generates waypoints with random latitude and longitude values within the
+    /// specified range and assigns unique labels to each waypoint. It also calculates the
+    /// geohash for each waypoint and inserts it into a geohash index for quick spatial
+    /// lookups.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    /// ```
+    pub fn generate_waypoints(&mut self, amt: usize) {
+        self.generate_waypoints_with(amt, RngKind::XorShift, clock_seed());
+    }
+
+    /// Randomly generates waypoints in the same manner as `generate_waypoints`, but using
+    /// the chosen pseudorandom number generator and seed. The same generator and seed will
+    /// always produce the same waypoints, which allows the distributions produced by each
+    /// generator to be compared.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// - `kind`: The pseudorandom number generator used to produce coordinates.
+    /// - `seed`: The initial seed value for the generator. Any seed may be used, including `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::RngKind;
+    ///
+    /// let mut xorshift_dataset = zpath::Dataset::new();
+    /// xorshift_dataset.generate_waypoints_with(10, RngKind::XorShift, 42);
+    ///
+    /// let mut lcg_dataset = zpath::Dataset::new();
+    /// lcg_dataset.generate_waypoints_with(10, RngKind::Lcg, 42);
+    /// ```
+    pub fn generate_waypoints_with(&mut self, amt: usize, kind: RngKind, seed: u64) {
+        self.generate_and_process(amt, kind, seed, |_, _| {});
+    }
+
+    /// Randomly generates waypoints in the same manner as `generate_waypoints`, labeled
+    /// with the chosen scheme rather than with letters, e.g. to give waypoints identifiers
+    /// that can be joined against an external system.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// - `scheme`: The scheme used to label each waypoint from its index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::LabelScheme;
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_labeled(3, LabelScheme::Prefixed(String::from("WP-")));
+    /// dataset.generate_waypoints_labeled(2, LabelScheme::Numeric { width: 3 });
+    ///
+    /// let labels: Vec<&str> = dataset.waypoints.iter().map(|w| w.label.as_str()).collect();
+    /// assert_eq!(labels, vec!["WP-0000", "WP-0001", "WP-0002", "003", "004"]);
+    /// ```
+    pub fn generate_waypoints_labeled(&mut self, amt: usize, scheme: LabelScheme) {
+        self.generate_labeled(amt, RngKind::XorShift, clock_seed(), &scheme, |_, _| {});
+    }
+
+    /// Randomly generates waypoints in the same manner as `generate_waypoints_with` using
+    /// the XorShift generator, and measures how long generation and indexing took. Useful
+    /// for benchmarking without having to time the call by hand.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// - `seed`: The initial seed value for the generator.
+    ///
+    /// # Returns
+    ///
+    /// - `Duration`: The time taken to generate and index the waypoints.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let elapsed = dataset.generate_waypoints_timed(1000, 42);
+    ///
+    /// println!("Generated 1000 waypoints in {:?}", elapsed);
+    /// assert_eq!(dataset.waypoints.len(), 1000);
+    /// ```
+    pub fn generate_waypoints_timed(&mut self, amt: usize, seed: u64) -> Duration {
+        let start = Instant::now();
+        self.generate_waypoints_with(amt, RngKind::XorShift, seed);
+        start.elapsed()
+    }
+
+    /// Randomly generates waypoints in the same manner as `generate_waypoints_with`, calling
+    /// `process` on each waypoint as soon as it has been created and indexed. This allows
+    /// waypoints to be handled incrementally (e.g. streamed to disk) while a large dataset is
+    /// being generated, rather than only once generation has finished. The waypoints are
+    /// still retained in the dataset.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// - `kind`: The pseudorandom number generator used to produce coordinates.
+    /// - `seed`: The initial seed value for the generator.
+    /// - `process`: A closure called with the index and a reference to each new waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use zpath::RngKind;
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// let mut csv = String::new();
+    ///
+    /// dataset.generate_and_process(10, RngKind::XorShift, 42, |_, waypoint| {
+    ///     csv.push_str(&format!("{},{},{}\n", waypoint.label, waypoint.lat, waypoint.lon));
+    /// });
+    ///
+    /// assert_eq!(csv.lines().count(), 10);
+    /// ```
+    pub fn generate_and_process<F>(&mut self, amt: usize, kind: RngKind, seed: u64, process: F)
+    where
+        F: FnMut(usize, &Waypoint),
+    {
+        self.generate_labeled(amt, kind, seed, &LabelScheme::Alpha, process);
+    }
+
+    /// Randomly generates waypoints in the same manner as `generate_and_process`, labeling
+    /// them with the given scheme.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of waypoints to generate and add to the dataset.
+    /// - `kind`: The pseudorandom number generator used to produce coordinates.
+    /// - `seed`: The initial seed value for the generator.
+    /// - `scheme`: The scheme used to label each waypoint from its index.
+    /// - `process`: A closure called with the index and a reference to each new waypoint.
+    fn generate_labeled<F>(
+        &mut self,
+        amt: usize,
+        kind: RngKind,
+        seed: u64,
+        scheme: &LabelScheme,
+        mut process: F,
+    ) where
+        F: FnMut(usize, &Waypoint),
+    {
+        let mut xorshift_rng = pseudo_random::XorShiftRng::new(seed);
+        let mut lcg_rng = pseudo_random::LcgRng::new(seed);
+
+        let waypoints_length = self.waypoints.len();
+
+        for i in 0..amt {
+            let label = scheme.label(waypoints_length + i);
+            let (lat, lon) = match kind {
+                RngKind::XorShift => (
+                    xorshift_rng.random_f32_in_range(-90.0, 90.0),
+                    xorshift_rng.random_f32_in_range(-180.0, 180.0),
+                ),
+                RngKind::Lcg => (
+                    lcg_rng.random_f32_in_range(-90.0, 90.0),
+                    lcg_rng.random_f32_in_range(-180.0, 180.0),
+                ),
+            };
+            let (lat, lon) = (Coord::from(lat), Coord::from(lon));
+            let waypoint = Waypoint::with_precision(lat, lon, label, self.precision);
+
+            self.geohash_index
+                .insert(&waypoint.geohash, waypoints_length + i);
+            self.waypoints.push(waypoint);
+
+            process(waypoints_length + i, &self.waypoints[waypoints_length + i]);
+        }
+    }
+
+    /// Creates a new waypoint with the specified latitude and longitude inserts it into the dataset.
+    /// Coordinates that aren't finite (NaN or infinite) are rejected, as they would give the
+    /// waypoint a meaningless geohash and a NaN distance to every other waypoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `lat` - The latitude coordinate of the new waypoint in degrees.
+    /// * `lon` - The longitude coordinate of the new waypoint in degrees.
+    ///
+    /// # Returns
+    ///
+    /// The index of the new waypoint, or `ZpathError::InvalidCoordinates` if either coordinate
+    /// isn't finite, in which case the dataset is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    ///
+    /// let rejected = dataset.add_new_waypoint(f32::NAN.into(), 0.0);
+    /// assert!(matches!(rejected, Err(zpath::ZpathError::InvalidCoordinates(..))));
+    /// assert_eq!(dataset.waypoints.len(), 1);
+    ///
+    /// // In a directed dataset, existing waypoints don't connect back to the new one
+    /// dataset.add_new_waypoint(37.8044, -122.2712).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    /// let new = dataset.add_new_waypoint(37.7, -122.3).unwrap();
+    /// assert_eq!(dataset.waypoints[new].connections.len(), 1);
+    /// assert!(dataset.waypoints[..new]
+    ///     .iter()
+    ///     .all(|waypoint| waypoint.connections.iter().all(|c| c.waypoint_index != new)));
+    /// ```
+    pub fn add_new_waypoint(&mut self, lat: Coord, lon: Coord) -> Result<usize, ZpathError> {
+        if !lat.is_finite() || !lon.is_finite() {
+            return Err(ZpathError::InvalidCoordinates(lat, lon));
+        }
+
+        let index = self.waypoints.len();
+        let waypoint =
+            Waypoint::with_precision(lat, lon, Waypoint::generate_label(index), self.precision);
+
+        self.geohash_index.insert(&waypoint.geohash, index);
+        self.waypoints.push(waypoint);
+
+        // If the dataset has already established connections, then assign some
+        // connections to the new waypoint
+        if !self.waypoints[0].connections.is_empty() {
+            let new_connections =
+                self.get_knn_geohash_by_index(index, self.waypoints[0].connections.len());
+
+            // Neighbors only connect back to the new waypoint if the dataset is undirected
+            if !self.directed {
+                for connection in &new_connections {
+                    self.waypoints[connection.waypoint_index]
+                        .connections
+                        .push(Connection::new(index, connection.distance))
+                }
+            }
+
+            self.waypoints[index].connections.extend(new_connections);
+        }
+
+        Ok(index)
+    }
+
+    /// Soft-deletes a waypoint: it stays in the dataset at the same index, but is no longer
+    /// returned by spatial queries, and routes and reachability searches no longer pass through
+    /// it. Unlike removing the waypoint, this leaves every other waypoint's index, and every
+    /// connection, valid. Connections to and from the waypoint are kept, so reactivating it
+    /// with `activate` restores it exactly.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of the waypoint to deactivate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let closed = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let goal = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    /// dataset.normalize_connections();
+    ///
+    /// dataset.deactivate(closed);
+    /// assert_eq!(dataset.get_knn_naive_by_index(start, 1)[0].waypoint_index, goal);
+    /// assert_eq!(dataset.get_shortest_route_by_index(start, goal), None);
+    /// assert_eq!(dataset.query_bbox(-1.0, -1.0, 1.0, 3.0), vec![start, goal]);
+    /// assert_eq!(dataset.get_farthest(&dataset.waypoints[goal], 2, Some(goal)).len(), 1);
+    /// assert!(dataset.close_pairs(200.0).is_empty());
+    /// assert!(dataset.reachable_within(closed, f32::INFINITY).is_empty());
+    ///
+    /// dataset.activate(closed);
+    /// assert_eq!(
+    ///     dataset.get_shortest_route_by_index(start, goal),
+    ///     Some(vec![start, closed, goal])
+    /// );
+    /// ```
+    pub fn deactivate(&mut self, index: usize) {
+        self.waypoints[index].active = false;
+    }
+
+    /// Restores a waypoint soft-deleted with `deactivate`, so that it's returned by searches
+    /// and can be routed through again.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of the waypoint to activate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    pub fn activate(&mut self, index: usize) {
+        self.waypoints[index].active = true;
+    }
+
+    /// Clears the geohash index and repopulates it from the current waypoints. Each waypoint's
+    /// geohash is re-encoded from its latitude and longitude first, so any stale geohashes
+    /// are corrected as well. Must be called after any direct mutation of `waypoints`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let c = dataset.add_new_waypoint(50.0, 50.0).unwrap();
+    ///
+    /// // Move waypoint C right next to waypoint A
+    /// dataset.waypoints[c].lat = 0.0;
+    /// dataset.waypoints[c].lon = 0.5;
+    /// dataset.rebuild_index();
+    ///
+    /// let nearest = dataset.get_knn_geohash(&dataset.waypoints[a], 1, Some(a));
+    /// assert_eq!(nearest[0].waypoint_index, c);
+    /// ```
+    pub fn rebuild_index(&mut self) {
+        self.geohash_index = Trie::new();
+
+        for (i, waypoint) in self.waypoints.iter_mut().enumerate() {
+            waypoint.geohash =
+                geohash::encode_unchecked(waypoint.lat, waypoint.lon, self.precision);
+            self.geohash_index.insert(&waypoint.geohash, i);
+        }
+    }
+
+    /// Moves every waypoint in the dataset a random distance of up to `max_km` in a random
+    /// direction, then rebuilds the geohash index. Useful for testing how sensitive routes
+    /// and nearest neighbor results are to small errors in the input. The same seed always
+    /// moves the waypoints in the same way.
+    ///
+    /// Connections are left untouched, so their distances no longer match the waypoints'
+    /// new positions; reassign them after jittering if that matters.
+    ///
+    /// # Parameters
+    ///
+    /// - `max_km`: The furthest any waypoint may be moved, in kilometers.
+    /// - `seed`: The seed for the random number generator. Any seed may be used, including `0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    /// let original = dataset.waypoints.clone();
+    ///
+    /// dataset.jitter(5.0, 42);
+    ///
+    /// for (before, after) in original.iter().zip(&dataset.waypoints) {
+    ///     assert!(before.get_distance_to(after) <= 5.01);
+    /// }
+    /// assert!(dataset.verify_geohashes().is_empty());
+    ///
+    /// // A seed of 0 moves the waypoints as well
+    /// let moved = dataset.waypoints.clone();
+    /// dataset.jitter(5.0, 0);
+    /// assert!(moved.iter().zip(&dataset.waypoints).any(|(a, b)| a.get_distance_to(b) > 0.0));
+    /// ```
+    pub fn jitter(&mut self, max_km: f32, seed: u64) {
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        // `destination` travels on a sphere of `EARTH_RADIUS`; scale to the dataset's radius
+        let scale = EARTH_RADIUS / self.earth_radius;
+
+        for waypoint in &mut self.waypoints {
+            let bearing = rng.random_f32_in_range(0.0, 360.0);
+            let distance = rng.random_f32_in_range(0.0, max_km);
+            (waypoint.lat, waypoint.lon) = waypoint.destination(bearing, distance * scale);
+        }
+
+        self.rebuild_index();
+    }
+
+    /// Thins the dataset to at most `max_per_cell` waypoints in each geohash cell of the
+    /// given precision, choosing the waypoints to keep at random. Unlike removing waypoints
+    /// uniformly at random, this leaves sparse regions untouched and only thins the dense
+    /// ones. The same seed always keeps the same waypoints.
+    ///
+    /// The remaining waypoints keep their relative order but are renumbered, so their
+    /// connections are updated to match and any connections to removed waypoints are
+    /// dropped. The geohash index is rebuilt afterwards.
+    ///
+    /// The remaining waypoints keep their labels, except for any with a label that
+    /// `add_new_waypoint` would generate again for a waypoint added later, as it labels new
+    /// waypoints by index. Those are relabeled with the first generated label not in use, so
+    /// labels stay unique.
+    ///
+    /// # Parameters
+    ///
+    /// - `precision`: The length of the geohash prefix used to group waypoints into cells.
+    /// - `max_per_cell`: The most waypoints to keep in each cell.
+    /// - `seed`: The seed for the XorShift generator that picks the waypoints to keep.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The number of waypoints removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// let mut lat = 10.0;
+    /// for _ in 0..10 {
+    ///     dataset.add_new_waypoint(lat, 10.0).unwrap();
+    ///     lat += 0.001;
+    /// }
+    /// dataset.add_new_waypoint(40.0, 40.0).unwrap();
+    ///
+    /// assert_eq!(dataset.downsample_per_cell(4, 3, 42), 7);
+    /// assert_eq!(dataset.waypoints.len(), 4);
+    /// assert!(dataset.waypoints.iter().any(|waypoint| waypoint.lat == 40.0));
+    /// assert!(dataset.verify_geohashes().is_empty());
+    ///
+    /// // A waypoint added afterwards is labeled by its index, "C", which the remaining
+    /// // waypoint at (40, 40) held; that waypoint was relabeled to keep labels unique
+    /// let mut pair = zpath::Dataset::new();
+    /// pair.add_new_waypoint(10.0, 10.0).unwrap();
+    /// pair.add_new_waypoint(10.0, 10.0).unwrap();
+    /// pair.add_new_waypoint(40.0, 40.0).unwrap();
+    /// assert_eq!(pair.downsample_per_cell(4, 1, 42), 1);
+    ///
+    /// let added = pair.add_new_waypoint(-40.0, -40.0).unwrap();
+    /// assert_eq!(pair.waypoints[added].label, "C");
+    /// let labels: HashSet<&str> = pair.iter().map(|(_, w)| w.label.as_str()).collect();
+    /// assert_eq!(labels.len(), 3);
+    /// ```
+    pub fn downsample_per_cell(
+        &mut self,
+        precision: usize,
+        max_per_cell: usize,
+        seed: u64,
+    ) -> usize {
+        // Group by cell in a sorted map, so the same seed visits the cells in the same order
+        let mut cells: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            let cell = geohash::encode_iter(waypoint.lat, waypoint.lon)
+                .take(precision)
+                .collect();
+            cells.entry(cell).or_default().push(i);
+        }
+
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        let mut keep = vec![false; self.waypoints.len()];
+
+        for members in cells.values_mut() {
+            // Partially shuffle the cell so its first `max_per_cell` members are a random pick
+            let kept = max_per_cell.min(members.len());
+            for i in 0..kept {
+                let j = i + rng.next_u32() as usize % (members.len() - i);
+                members.swap(i, j);
+                keep[members[i]] = true;
+            }
+        }
+
+        let mut new_indices = vec![None; self.waypoints.len()];
+        let mut next_index = 0;
+        for (i, &kept) in keep.iter().enumerate() {
+            if kept {
+                new_indices[i] = Some(next_index);
+                next_index += 1;
+            }
+        }
+
+        let removed = self.waypoints.len() - next_index;
+        let mut kept = keep.iter();
+        self.waypoints.retain(|_| *kept.next().unwrap());
+
+        for waypoint in &mut self.waypoints {
+            waypoint.connections.retain_mut(|connection| {
+                match new_indices[connection.waypoint_index] {
+                    Some(new_index) => {
+                        connection.waypoint_index = new_index;
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+
+        // `add_new_waypoint` labels new waypoints by index, so relabel any waypoint holding
+        // the label it would generate for an index beyond the remaining waypoints
+        let len = self.waypoints.len();
+        let mut labels: HashSet<String> = self.waypoints.iter().map(|w| w.label.clone()).collect();
+        let mut n = 0;
+        for waypoint in &mut self.waypoints {
+            if Waypoint::generated_label_index(&waypoint.label).is_some_and(|index| index >= len) {
+                while labels.contains(&Waypoint::generate_label(n)) {
+                    n += 1;
+                }
+                waypoint.label = Waypoint::generate_label(n);
+                labels.insert(waypoint.label.clone());
+            }
+        }
+
+        self.rebuild_index();
+        removed
+    }
+
+    /// Checks that each waypoint's stored geohash matches its latitude and longitude. A
+    /// mismatch means the waypoint's coordinates were changed without calling
+    /// `rebuild_index`, and the geohash index will give wrong results for it.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints whose geohash doesn't match their
+    ///   coordinates, in ascending order. Empty if every geohash is correct.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// assert!(dataset.verify_geohashes().is_empty());
+    ///
+    /// dataset.waypoints[b].lat = 45.0;
+    /// assert_eq!(dataset.verify_geohashes(), vec![b]);
+    ///
+    /// dataset.rebuild_index();
+    /// assert!(dataset.verify_geohashes().is_empty());
+    /// ```
+    pub fn verify_geohashes(&self) -> Vec<usize> {
+        self.iter()
+            .filter(|(_, waypoint)| {
+                waypoint.geohash
+                    != geohash::encode_unchecked(waypoint.lat, waypoint.lon, self.precision)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Counts the total number of nodes in the dataset's geohash index, including its root.
+    /// Useful for measuring how much memory the index occupies.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The number of nodes in the geohash index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    ///
+    /// dataset.add_new_waypoint(37.7750, -122.4195).unwrap();
+    ///
+    /// // The root, one node for the geohash prefix the waypoints share, and one for each waypoint
+    /// assert_eq!(dataset.index_node_count(), 4);
+    /// ```
+    pub fn index_node_count(&self) -> usize {
+        self.geohash_index.count_nodes()
+    }
+
+    /// Finds the depth of the dataset's geohash index; the number of nodes in the longest
+    /// chain from the root (exclusive) to a leaf.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The maximum depth of the geohash index; `0` if the dataset is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(37.7750, -122.4195).unwrap();
+    ///
+    /// assert_eq!(dataset.index_max_depth(), 2);
+    /// ```
+    pub fn index_max_depth(&self) -> usize {
+        self.geohash_index.max_depth()
+    }
+
+    /// Builds a histogram of how crowded the cells of the dataset's geohash index are, by
+    /// counting the waypoints in each full-precision geohash cell. A long tail of crowded
+    /// cells indicates heavily clustered waypoints, which slow down geohash searches.
+    ///
+    /// # Returns
+    ///
+    /// - `HashMap<usize, usize>`: A map from the number of waypoints in a cell to the number of
+    ///   cells holding that many waypoints. Empty cells aren't counted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    /// dataset.add_new_waypoint(51.5074, -0.1278).unwrap();
+    ///
+    /// let histogram = dataset.index_histogram();
+    /// assert_eq!(histogram[&1], 2);
+    /// assert_eq!(histogram[&3], 1);
+    ///
+    /// // Waypoints sharing a geohash can all be found through the index
+    /// assert_eq!(dataset.waypoints_in_cell(&dataset.waypoints[0].geohash).count(), 3);
+    /// ```
+    pub fn index_histogram(&self) -> HashMap<usize, usize> {
+        let mut histogram = HashMap::new();
+        self.geohash_index.tally_occupancy(&mut histogram);
+        histogram
+    }
+
+    /// Writes the geohash index to a compact binary form, so that it can be restored with
+    /// `load_index` rather than rebuilt from every waypoint, e.g. when restarting a server
+    /// over unchanged data. Only the index is written; the waypoints must be saved separately.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: The destination of the index, e.g. a file.
+    ///
+    /// # Returns
+    ///
+    /// - `io::Result<()>`: Any error from the writer, or `io::ErrorKind::InvalidData` if the
+    ///   dataset has more waypoints than fit in 32-bit indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(1000);
+    ///
+    /// let mut saved = Vec::new();
+    /// dataset.save_index(&mut saved).unwrap();
+    ///
+    /// // Restore the index alongside a copy of the waypoints, without rebuilding it
+    /// let mut restored = zpath::Dataset::new();
+    /// restored.waypoints = dataset.waypoints.clone();
+    /// restored.load_index(saved.as_slice()).unwrap();
+    ///
+    /// assert_eq!(restored.index_node_count(), dataset.index_node_count());
+    /// assert_eq!(restored.get_knn_geohash_by_index(0, 5), dataset.get_knn_geohash_by_index(0, 5));
+    ///
+    /// // An index referring to waypoints that don't exist is rejected
+    /// let mut smaller = zpath::Dataset::new();
+    /// smaller.generate_waypoints(10);
+    /// assert!(smaller.load_index(saved.as_slice()).is_err());
+    /// ```
+    pub fn save_index(&self, writer: impl io::Write) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        writer.write_all(INDEX_MAGIC)?;
+        self.geohash_index.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Replaces the geohash index with one written by `save_index`, skipping the work of
+    /// encoding and inserting every waypoint's geohash. Every waypoint index in the saved
+    /// index is checked to refer to one of the dataset's waypoints, but the index is
+    /// otherwise trusted to match them; use `rebuild_index` if the waypoints may have changed
+    /// since it was saved.
+    ///
+    /// Exactly the bytes of the saved index are read and no more, so the index may be
+    /// followed by other data in the same stream. The index is read in many small pieces,
+    /// so a slow source such as a `File` should be wrapped in an `io::BufReader` first;
+    /// pass the `BufReader` by `&mut` to carry on reading from it afterwards.
+    ///
+    /// # Parameters
+    ///
+    /// - `reader`: The source of the saved index.
+    ///
+    /// # Returns
+    ///
+    /// - `io::Result<()>`: Any error from the reader, or `io::ErrorKind::InvalidData` if the
+    ///   data isn't a valid saved index or refers to waypoints that don't exist. The current
+    ///   index is left in place if loading fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    ///
+    /// let mut saved = Vec::new();
+    /// dataset.save_index(&mut saved).unwrap();
+    /// saved.extend_from_slice(b"trailer");
+    ///
+    /// // The data following the index is left unread
+    /// let mut reader = saved.as_slice();
+    /// dataset.load_index(&mut reader).unwrap();
+    /// let mut rest = String::new();
+    /// reader.read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "trailer");
+    ///
+    /// // A path deeper than any geohash is rejected rather than recursed into
+    /// let mut deep = b"ZPI1".to_vec();
+    /// for _ in 0..100_000 {
+    ///     deep.extend_from_slice(&[1, b'0', 0, 0, 0, 0, 1]);
+    /// }
+    /// assert!(dataset.load_index(deep.as_slice()).is_err());
+    ///
+    /// // As is a chain of nodes with empty labels, which wouldn't lengthen the path
+    /// let mut empty = b"ZPI1".to_vec();
+    /// for _ in 0..1_000_000 {
+    ///     empty.extend_from_slice(&[0, 0, 0, 0, 0, 1]);
+    /// }
+    /// assert!(dataset.load_index(empty.as_slice()).is_err());
+    /// ```
+    pub fn load_index(&mut self, mut reader: impl io::Read) -> io::Result<()> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != INDEX_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a saved geohash index",
+            ));
+        }
+
+        self.geohash_index = Trie::read_from(&mut reader, self.waypoints.len(), 0, None)?;
+        Ok(())
+    }
+
+    /// Merges another dataset into this one by appending its waypoints. The indices in the
+    /// merged waypoints' connections are offset to point at their new positions, and their
+    /// geohashes are inserted into this dataset's geohash index. Any merged waypoint whose
+    /// label is already in use is relabeled with the next unused generated label. No
+    /// connections are created between the two datasets' waypoints until connections are
+    /// assigned again.
+    ///
+    /// # Parameters
+    ///
+    /// - `other`: The dataset to merge into this one.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The offset added to each of `other`'s waypoint indices; a waypoint at index
+    ///   `i` in `other` is at index `offset + i` in the merged dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut east = zpath::Dataset::new();
+    /// east.add_new_waypoint(40.7128, -74.0060).unwrap();
+    ///
+    /// let mut west = zpath::Dataset::new();
+    /// west.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// west.add_new_waypoint(34.0522, -118.2437).unwrap();
+    /// west.assign_all_connections_naive(1);
+    ///
+    /// let offset = east.merge(west);
+    /// assert_eq!(offset, 1);
+    /// assert_eq!(east.waypoints.len(), 3);
+    /// assert_eq!(east.waypoints[1].label, "B");
+    /// assert_eq!(east.waypoints[1].connections[0].waypoint_index, 2);
+    /// ```
+    pub fn merge(&mut self, other: Dataset) -> usize {
+        let offset = self.waypoints.len();
+        let mut labels: HashSet<String> = self.waypoints.iter().map(|w| w.label.clone()).collect();
+
+        for (i, mut waypoint) in other.waypoints.into_iter().enumerate() {
+            let index = offset + i;
+
+            if labels.contains(&waypoint.label) {
+                let mut n = index;
+                while labels.contains(&Waypoint::generate_label(n)) {
+                    n += 1;
+                }
+                waypoint.label = Waypoint::generate_label(n);
+            }
+
+            for connection in &mut waypoint.connections {
+                connection.waypoint_index += offset;
+            }
+
+            // The other dataset may index its waypoints at a different precision
+            if waypoint.geohash.len() != self.precision {
+                waypoint.geohash =
+                    geohash::encode_unchecked(waypoint.lat, waypoint.lon, self.precision);
+            }
+
+            labels.insert(waypoint.label.clone());
+            self.geohash_index.insert(&waypoint.geohash, index);
+            self.waypoints.push(waypoint);
+        }
+
+        offset
+    }
+
+    /// Searches for a waypoint with a matching label within the dataset and
+    /// returns `Some(index)` if found.
+    ///
+    /// # Parameters
+    ///
+    /// - `waypoint`: A reference to the waypoint whose index should be retrieved.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<usize>`: The index of the waypoint within the dataset if found.
+    /// - `None`: If the waypoint isn't found, it returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    /// let waypoint_a = dataset.waypoints[0];
+    ///
+    /// match dataset.get_waypoint_index(&waypoint_a) {
+    ///     Some(index) => println!("Index of waypoint: {}", index),
+    ///     None => println!("Waypoint not found in the dataset."),
+    /// }
+    /// ```
+    fn get_waypoint_index(&self, waypoint: &Waypoint) -> Option<usize> {
+        self.waypoints
+            .iter()
+            .position(|x| x.label == waypoint.label)
+    }
+
+    /// Finds the index of a waypoint within the dataset, as `get_waypoint_index` does, for
+    /// operations that can't continue without it.
+    ///
+    /// # Parameters
+    ///
+    /// - `waypoint`: A reference to the waypoint to search for.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<usize, ZpathError>`: The index of the waypoint, or
+    ///   `ZpathError::WaypointNotFound` if it isn't part of the dataset.
+    fn require_waypoint_index(&self, waypoint: &Waypoint) -> Result<usize, ZpathError> {
+        self.get_waypoint_index(waypoint)
+            .ok_or_else(|| ZpathError::WaypointNotFound(waypoint.label.clone()))
+    }
+
+    /// Queries the geohash index to retrieve all waypoint indices that share a common
+    /// geohash prefix with the specified geohash. It is used to find waypoints within the same
+    /// geohash cell or adjacent cells.
+    ///
+    /// # Parameters
+    ///
+    /// - `geohash`: A reference to the geohash prefix to search for.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: A vector containing the indices of waypoints matching the geohash prefix.
+    ///   Empty if none.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let geohash_prefix = "9t37";
+    /// let matching_indices = dataset.search_geohash(geohash_prefix);
+    ///
+    /// for index in matching_indices {
+    ///     println!("Matching Waypoint: {:?}", dataset.waypoints[index].label);
+    /// }
+    /// ```
+    fn search_geohash(&self, geohash: &str) -> Vec<usize> {
+        self.geohash_index.get_all_with_prefix(geohash)
+    }
+
+    /// Lazily iterates over the indices of all waypoints whose geohash begins with the
+    /// specified prefix, without collecting them into an intermediate vector. Useful when
+    /// scanning many cells and discarding most of the results.
+    ///
+    /// # Parameters
+    ///
+    /// - `prefix`: A reference to the geohash prefix of the cell to search.
+    ///
+    /// # Returns
+    ///
+    /// - `impl Iterator<Item = usize>`: An iterator over the indices of the matching waypoints,
+    ///   in no particular order. Empty if none.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(37.7750, -122.4195).unwrap();
+    /// dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    ///
+    /// assert_eq!(dataset.waypoints_in_cell("9q8").count(), 2);
+    /// assert_eq!(dataset.waypoints_in_cell("").count(), 3);
+    /// assert_eq!(dataset.waypoints_in_cell("zzz").count(), 0);
+    /// ```
+    pub fn waypoints_in_cell(&self, prefix: &str) -> impl Iterator<Item = usize> + '_ {
+        self.geohash_index
+            .find_prefix(prefix)
+            .into_iter()
+            .flat_map(|node| node.iter())
+    }
+
+    /// Partitions the waypoints into geographic shards by the leading characters of their
+    /// geohashes, e.g. to process each region independently or on separate machines. Each
+    /// shard covers a single geohash cell, so shorter prefixes give fewer, larger shards.
+    ///
+    /// # Parameters
+    ///
+    /// - `prefix_len`: The number of leading geohash characters shared by each shard's
+    ///   waypoints. Prefixes longer than the waypoints' geohashes are treated as the whole
+    ///   geohash.
+    ///
+    /// # Returns
+    ///
+    /// - `HashMap<String, Vec<usize>>`: The indices of the waypoints in each shard, in
+    ///   ascending order, keyed by the shard's geohash prefix. Every waypoint belongs to
+    ///   exactly one shard.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(37.8044, -122.2712).unwrap();
+    /// dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    ///
+    /// let shards = dataset.shard_by_prefix(2);
+    /// assert_eq!(shards.len(), 2);
+    /// assert_eq!(shards["9q"], vec![0, 1]);
+    /// assert_eq!(shards["r3"], vec![2]);
+    /// ```
+    pub fn shard_by_prefix(&self, prefix_len: usize) -> HashMap<String, Vec<usize>> {
+        let mut shards: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            let prefix_len = prefix_len.min(waypoint.geohash.len());
+            shards
+                .entry(String::from(&waypoint.geohash[..prefix_len]))
+                .or_default()
+                .push(i);
+        }
+
+        shards
+    }
+
+    /// Orders the waypoints along a space-filling curve, so that waypoints near each other
+    /// tend to be near each other in the order, e.g. to rearrange other arrays for better
+    /// cache locality when iterating over neighboring waypoints. The waypoints are sorted by
+    /// geohash; as the characters of the geohash alphabet are in the same order as the bits
+    /// they encode, this is the Z-order (Morton) curve at the dataset's geohash precision.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: Every waypoint index, in order along the curve. Waypoints sharing a
+    ///   geohash are kept in ascending index order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let sydney = dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    /// let san_francisco = dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// let melbourne = dataset.add_new_waypoint(-37.8136, 144.9631).unwrap();
+    /// let oakland = dataset.add_new_waypoint(37.8044, -122.2712).unwrap();
+    ///
+    /// // Nearby cities end up next to each other
+    /// let order = dataset.spatial_sort_order();
+    /// assert_eq!(order, vec![san_francisco, oakland, melbourne, sydney]);
+    /// ```
+    pub fn spatial_sort_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.waypoints.len()).collect();
+        order.sort_by(|&a, &b| self.waypoints[a].geohash.cmp(&self.waypoints[b].geohash));
+        order
+    }
+
+    /// Calculates the K-nearest neighbors to a specified waypoint within the dataset
+    /// using a naive approach that iterates through all waypoints in the dataset,
+    /// calculates its distance to all other waypoints in the dataset, sorts them,
+    /// and returns the nearest K.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by
+    ///   distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let waypoint_a = &dataset.waypoints[0];
+    /// let k = 3;
+    /// let nearest_neighbors = dataset.get_knn_naive(waypoint_a, k, Some(0));
+    ///
+    /// for neighbor in nearest_neighbors {
+    ///     println!(
+    ///         "Neighbor: {:?} - {:.2}km",
+    ///         dataset.waypoints[neighbor.waypoint_index].label,
+    ///         neighbor.distance
+    ///     );
+    /// }
+    /// ```
+    pub fn get_knn_naive(
+        &self,
+        target: &Waypoint,
+        k: usize,
+        exclude: Option<usize>,
+    ) -> Vec<Connection> {
+        let mut nearest_neighbors: Vec<Connection> = Vec::new();
+
+        for (i, neighbor) in self.waypoints.iter().enumerate() {
+            if exclude != Some(i) && neighbor.active {
+                nearest_neighbors.push(Connection::new(i, self.metric_distance(target, neighbor)))
+            }
+        }
+
+        nearest_neighbors.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        nearest_neighbors.truncate(k);
+        nearest_neighbors
+    }
+
+    /// Calculates the K-nearest neighbors to the waypoint at the given index in the same
+    /// manner as `get_knn_naive`.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let nearest_neighbors = dataset.get_knn_naive_by_index(0, 3);
+    /// assert_eq!(nearest_neighbors, dataset.get_knn_naive(&dataset.waypoints[0], 3, Some(0)));
+    /// ```
+    pub fn get_knn_naive_by_index(&self, index: usize, k: usize) -> Vec<Connection> {
+        self.get_knn_naive(&self.waypoints[index], k, Some(index))
+    }
+
+    /// Finds the waypoints within the dataset that are farthest from a specified waypoint by
+    /// scanning every waypoint and sorting them by distance. The geohash index can't prune
+    /// the search for the farthest waypoints, so this always takes O(n log n) time.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint to measure distances from.
+    /// - `n`: The number of waypoints to retrieve.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing connections to the `n` farthest active
+    ///   waypoints, sorted by distance in descending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let home = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 10.0).unwrap();
+    /// let far = dataset.add_new_waypoint(0.0, 170.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, -20.0).unwrap();
+    ///
+    /// let farthest = dataset.get_farthest(&dataset.waypoints[home], 2, Some(home));
+    /// assert_eq!(farthest.len(), 2);
+    /// assert_eq!(farthest[0].waypoint_index, far);
+    /// assert!(farthest[0].distance > farthest[1].distance);
+    /// ```
+    pub fn get_farthest(
+        &self,
+        target: &Waypoint,
+        n: usize,
+        exclude: Option<usize>,
+    ) -> Vec<Connection> {
+        let mut farthest: Vec<Connection> = Vec::new();
+
+        for (i, candidate) in self.waypoints.iter().enumerate() {
+            if exclude != Some(i) && candidate.active {
+                farthest.push(Connection::new(i, self.metric_distance(target, candidate)))
+            }
+        }
+
+        farthest.sort_by(|a, b| {
+            b.distance
+                .partial_cmp(&a.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        farthest.truncate(n);
+        farthest
+    }
+
+    /// Calculates the K-nearest neighbors to a specified waypoint within the dataset based on
+    /// geohash proximity. Uses a priority queue (binary heap) to efficiently find the nearest
+    /// neighbors. Waypoints just across the border of the searched geohash cell can be nearer
+    /// than those found inside it, so the area within the distance of the K-th nearest
+    /// neighbor is also checked; the result is the same as that of `get_knn_naive`.
+    ///
+    /// # Parameters
+    ///
+    /// - `waypoint`: A reference to the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `waypoint` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by
+    ///   distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let waypoint_a = &dataset.waypoints[0];
+    /// let k = 3;
+    /// let nearest_neighbors = dataset.get_knn_geohash(waypoint_a, k, Some(0));
+    ///
+    /// for neighbor in nearest_neighbors {
+    ///     println!(
+    ///         "Neighbor: {:?} - {:.2}km",
+    ///         dataset.waypoints[neighbor.waypoint_index].label,
+    ///         neighbor.distance
+    ///     );
+    /// }
+    ///
+    /// // The geohash search finds the same neighbors as checking every waypoint
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_with(2000, zpath::RngKind::XorShift, 42);
+    ///
+    /// for (i, waypoint) in dataset.waypoints.iter().enumerate() {
+    ///     let geohash_distances: Vec<f32> = dataset
+    ///         .get_knn_geohash(waypoint, 5, Some(i))
+    ///         .iter()
+    ///         .map(|neighbor| neighbor.distance)
+    ///         .collect();
+    ///     let naive_distances: Vec<f32> = dataset
+    ///         .get_knn_naive(waypoint, 5, Some(i))
+    ///         .iter()
+    ///         .map(|neighbor| neighbor.distance)
+    ///         .collect();
+    ///
+    ///     assert_eq!(geohash_distances, naive_distances);
+    /// }
+    /// ```
+    pub fn get_knn_geohash(
+        &self,
+        waypoint: &Waypoint,
+        k: usize,
+        exclude: Option<usize>,
+    ) -> Vec<Connection> {
+        self.knn_geohash(waypoint, exclude, k, |_| true, &mut || true)
+    }
+
+    /// Calculates the K-nearest neighbors to the waypoint at the given index in the same
+    /// manner as `get_knn_geohash`, without having to search the dataset for the waypoint.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let nearest_neighbors = dataset.get_knn_geohash_by_index(0, 3);
+    /// assert_eq!(nearest_neighbors.len(), 3);
+    /// assert!(nearest_neighbors.iter().all(|neighbor| neighbor.waypoint_index != 0));
+    /// ```
+    pub fn get_knn_geohash_by_index(&self, index: usize, k: usize) -> Vec<Connection> {
+        self.knn_geohash(
+            &self.waypoints[index],
+            Some(index),
+            k,
+            |_| true,
+            &mut || true,
+        )
+    }
+
+    /// Calculates the K-nearest neighbors to a specified waypoint among only those waypoints
+    /// matching a predicate, e.g. the nearest waypoints tagged as fuel stations in their
+    /// `metadata`. The search widens through geohash cells in the same manner as
+    /// `get_knn_geohash`, but only waypoints matching the predicate count towards K. If few
+    /// waypoints match, the search widens until it has covered the whole dataset and then
+    /// stops, returning however many matches it found.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    /// - `pred`: Returns `true` for the waypoints that may be counted as neighbors.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: The K-nearest matching neighbor connections, sorted by distance.
+    ///   Fewer than K if fewer waypoints match.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let home = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.1).unwrap();
+    /// let fuel = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.2).unwrap();
+    /// dataset.waypoints[fuel]
+    ///     .metadata
+    ///     .insert(String::from("category"), String::from("fuel"));
+    ///
+    /// let is_fuel = |waypoint: &zpath::Waypoint| {
+    ///     waypoint.metadata.get("category").is_some_and(|category| category == "fuel")
+    /// };
+    /// let nearest = dataset.get_knn_filtered(&dataset.waypoints[home], 3, Some(home), is_fuel);
+    /// assert_eq!(nearest.len(), 1);
+    /// assert_eq!(nearest[0].waypoint_index, fuel);
+    /// ```
+    pub fn get_knn_filtered<F: Fn(&Waypoint) -> bool>(
+        &self,
+        target: &Waypoint,
+        k: usize,
+        exclude: Option<usize>,
+        pred: F,
+    ) -> Vec<Connection> {
+        self.knn_geohash(target, exclude, k, pred, &mut || true)
+    }
+
+    /// Calculates the approximate K-nearest neighbors to a specified waypoint in the same
+    /// manner as `get_knn_geohash`, but randomly skips some of the surrounding geohash cells
+    /// checked for nearer neighbors, trading accuracy for speed. Use `knn_recall_sampled` to
+    /// measure how much accuracy a given rate costs.
+    ///
+    /// # Parameters
+    ///
+    /// - `waypoint`: A reference to the waypoint for which K-nearest neighbors are to be found.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `waypoint` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    /// - `sample_rate`: The chance of skipping each surrounding cell, from `0.0` (skip none,
+    ///   matching `get_knn_geohash`) to `1.0` (skip all).
+    /// - `seed`: The seed for the XorShift generator that picks the cells to skip.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: The approximate K-nearest neighbor connections, sorted by distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_with(500, zpath::RngKind::XorShift, 42);
+    /// let waypoint = &dataset.waypoints[0];
+    ///
+    /// let exact = dataset.get_knn_geohash(waypoint, 5, Some(0));
+    /// assert_eq!(dataset.get_knn_geohash_sampled(waypoint, 5, Some(0), 0.0, 7), exact);
+    /// assert_eq!(dataset.get_knn_geohash_sampled(waypoint, 5, Some(0), 0.5, 7).len(), 5);
+    /// ```
+    pub fn get_knn_geohash_sampled(
+        &self,
+        waypoint: &Waypoint,
+        k: usize,
+        exclude: Option<usize>,
+        sample_rate: f32,
+        seed: u64,
+    ) -> Vec<Connection> {
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        let mut keep_cell = || rng.random_f32_in_range(0.0, 1.0) >= sample_rate;
+
+        self.knn_geohash(waypoint, exclude, k, |_| true, &mut keep_cell)
+    }
+
+    /// Measures how closely the geohash K-nearest neighbor search agrees with the exact
+    /// results of `get_knn_naive`, over a random sample of waypoints. Useful for checking
+    /// that changes to the index or its precision haven't cost accuracy, and for measuring
+    /// the effect of a `Metric` that the geohash index can't account for.
+    ///
+    /// # Parameters
+    ///
+    /// - `sample`: The number of waypoints to sample, with replacement.
+    /// - `k`: The number of nearest neighbors to compare for each sampled waypoint.
+    /// - `seed`: The seed for the XorShift generator that picks the sample.
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The average fraction of each sampled waypoint's true K-nearest neighbors that
+    ///   the geohash search also found, from `0.0` to `1.0`. Waypoints at exactly the same
+    ///   distance as the K-th nearest may be swapped for one another, and count as misses.
+    ///   `1.0` if the sample is empty or there are no neighbors to find.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_with(2000, zpath::RngKind::XorShift, 42);
+    ///
+    /// assert_eq!(dataset.knn_recall(100, 5, 7), 1.0);
+    /// ```
+    pub fn knn_recall(&self, sample: usize, k: usize, seed: u64) -> f32 {
+        self.recall_against_naive(sample, k, seed, |index| {
+            self.get_knn_geohash_by_index(index, k)
+        })
+    }
+
+    /// Measures how closely the cell-skipping search of `get_knn_geohash_sampled` agrees with
+    /// the exact results of `get_knn_naive`, in the same manner as `knn_recall`.
+    ///
+    /// # Parameters
+    ///
+    /// - `sample`: The number of waypoints to sample, with replacement.
+    /// - `k`: The number of nearest neighbors to compare for each sampled waypoint.
+    /// - `sample_rate`: The chance of skipping each surrounding cell during the search.
+    /// - `seed`: The seed for the XorShift generators that pick the sample and the cells to
+    ///   skip.
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The average fraction of each sampled waypoint's true K-nearest neighbors that
+    ///   the sampled search also found, from `0.0` to `1.0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_with(2000, zpath::RngKind::XorShift, 42);
+    ///
+    /// assert_eq!(dataset.knn_recall_sampled(100, 5, 0.0, 7), 1.0);
+    /// assert!(dataset.knn_recall_sampled(100, 5, 0.9, 7) < 1.0);
+    /// ```
+    pub fn knn_recall_sampled(&self, sample: usize, k: usize, sample_rate: f32, seed: u64) -> f32 {
+        let mut rng = pseudo_random::XorShiftRng::new(seed.rotate_left(32));
+        let mut keep_cell = || rng.random_f32_in_range(0.0, 1.0) >= sample_rate;
+
+        self.recall_against_naive(sample, k, seed, |index| {
+            self.knn_geohash(
+                &self.waypoints[index],
+                Some(index),
+                k,
+                |_| true,
+                &mut keep_cell,
+            )
+        })
+    }
+
+    /// Averages the recall of a K-nearest neighbor search against `get_knn_naive_by_index`
+    /// over a random sample of waypoints (see `knn_recall`).
+    ///
+    /// # Parameters
+    ///
+    /// - `sample`: The number of waypoints to sample, with replacement.
+    /// - `k`: The number of nearest neighbors to compare for each sampled waypoint.
+    /// - `seed`: The seed for the XorShift generator that picks the sample.
+    /// - `search`: Returns the neighbors found for the waypoint at the given index.
+    ///
+    /// # Returns
+    ///
+    /// - `f32`: The average recall, from `0.0` to `1.0`.
+    fn recall_against_naive(
+        &self,
+        sample: usize,
+        k: usize,
+        seed: u64,
+        mut search: impl FnMut(usize) -> Vec<Connection>,
+    ) -> f32 {
+        if self.waypoints.is_empty() || sample == 0 {
+            return 1.0;
+        }
+
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        let mut total_recall = 0.0;
+
+        for _ in 0..sample {
+            let index = rng.next_u32() as usize % self.waypoints.len();
+
+            let exact: HashSet<usize> = self
+                .get_knn_naive_by_index(index, k)
+                .iter()
+                .map(|neighbor| neighbor.waypoint_index)
+                .collect();
+            if exact.is_empty() {
+                total_recall += 1.0;
+                continue;
+            }
+
+            let found = search(index)
+                .iter()
+                .filter(|neighbor| exact.contains(&neighbor.waypoint_index))
+                .count();
+            total_recall += found as f32 / exact.len() as f32;
+        }
+
+        total_recall / sample as f32
+    }
+
+    /// Finds the waypoint nearest to a location which needn't be a waypoint itself, e.g. to
+    /// snap a GPS reading onto the dataset. Uses the geohash search of `get_knn_geohash`.
+    ///
+    /// # Parameters
+    ///
+    /// - `lat`: The latitude of the location in degrees.
+    /// - `lon`: The longitude of the location in degrees.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<usize>`: The index of the nearest waypoint, or `None` if the dataset is empty
+    ///   or either coordinate isn't finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// let oakland = dataset.add_new_waypoint(37.8044, -122.2712).unwrap();
+    ///
+    /// assert_eq!(dataset.nearest_to_coord(37.8, -122.3), Some(oakland));
+    /// ```
+    pub fn nearest_to_coord(&self, lat: Coord, lon: Coord) -> Option<usize> {
+        self.nearest_to_coords(&[(lat, lon)])[0]
+    }
+
+    /// Finds the waypoint nearest to each of a list of locations in the same manner as
+    /// `nearest_to_coord`, e.g. to snap every point of a GPS trace onto the dataset. A single
+    /// probe waypoint is reused for every location rather than one being created for each.
+    ///
+    /// # Parameters
+    ///
+    /// - `coords`: The latitude and longitude of each location in degrees.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Option<usize>>`: The index of the waypoint nearest to each location, in the
+    ///   same order as `coords`. `None` for every location if the dataset is empty, and for
+    ///   any location with a coordinate that isn't finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let west = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let east = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    ///
+    /// let trace = [(0.1, 0.2), (0.0, 0.7), (f32::NAN.into(), 0.0), (-0.1, 0.4)];
+    /// assert_eq!(
+    ///     dataset.nearest_to_coords(&trace),
+    ///     vec![Some(west), Some(east), None, Some(west)]
+    /// );
+    /// ```
+    pub fn nearest_to_coords(&self, coords: &[(Coord, Coord)]) -> Vec<Option<usize>> {
+        let mut probe = Waypoint::with_precision(0.0, 0.0, String::new(), self.precision);
+
+        coords
+            .iter()
+            .map(|&(lat, lon)| {
+                if !lat.is_finite() || !lon.is_finite() {
+                    return None;
+                }
+
+                probe.lat = lat;
+                probe.lon = lon;
+                probe.geohash = geohash::encode_unchecked(lat, lon, self.precision);
+
+                self.knn_geohash(&probe, None, 1, |_| true, &mut || true)
+                    .first()
+                    .map(|nearest| nearest.waypoint_index)
+            })
+            .collect()
+    }
+
+    /// Runs the geohash K-nearest neighbor search underlying `get_knn_geohash`,
+    /// `get_knn_geohash_by_index` and `get_knn_filtered`.
+    ///
+    /// # Parameters
+    ///
+    /// - `waypoint`: A reference to the waypoint for which K-nearest neighbors are to be found.
+    /// - `exclude`: The index of the waypoint itself, if it's part of the dataset, so that it
+    ///   isn't counted as its own neighbor.
+    /// - `k`: The number of nearest neighbors to retrieve.
+    /// - `filter`: Returns whether a candidate waypoint may be counted as a neighbor.
+    /// - `keep_cell`: Called for each cell around the waypoint checked for nearer neighbors,
+    ///   returning whether to search it (see `get_knn_geohash_sampled`).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing the K-nearest neighbor connections, sorted by distance.
+    fn knn_geohash(
+        &self,
+        waypoint: &Waypoint,
+        exclude: Option<usize>,
+        k: usize,
+        filter: impl Fn(&Waypoint) -> bool,
+        keep_cell: &mut dyn FnMut() -> bool,
+    ) -> Vec<Connection> {
+        let mut geohash_to_search = waypoint.geohash.clone();
+        let mut min_heap: BinaryHeap<Connection> =
+            BinaryHeap::with_capacity(k.saturating_mul(2).min(self.waypoints.len()));
+        let mut visited: HashSet<usize> = HashSet::new();
+        if let Some(index) = exclude {
+            visited.insert(index);
+        }
+        let is_candidate = |index: usize| {
+            let neighbor = &self.waypoints[index];
+            neighbor.active && filter(neighbor)
+        };
+
+        // Stop widening once the whole dataset has been searched, even if k wasn't reached
+        while min_heap.len() < k && !geohash_to_search.is_empty() {
+            // Remove a level of precision and search the larger geohash cell for neighbors
+            geohash_to_search.pop();
+
+            for neighbor_index in self.search_geohash(&geohash_to_search) {
+                if visited.insert(neighbor_index) && is_candidate(neighbor_index) {
+                    min_heap.push(Connection::new(
+                        neighbor_index,
+                        self.metric_distance(waypoint, &self.waypoints[neighbor_index]),
+                    ))
+                }
+            }
+        }
+
+        // Convert binary heap to vector and truncate to nearest k elements
+        let mut nearest_neighbors = min_heap.into_sorted_vec();
+        nearest_neighbors.truncate(k);
+
+        // k neighbors have been found within the cell, but waypoints just across its border
+        // may be nearer. Any such waypoint lies within the distance of the k-th nearest, so
+        // only that area around the waypoint needs to be checked. If the search widened to
+        // the entire dataset, there is nothing left to check.
+        // The area is searched in kilometers, so the radius is the great-circle distance to the
+        // k-th nearest rather than its distance by the dataset's metric, which may differ.
+        let kth_nearest = nearest_neighbors
+            .last()
+            .filter(|_| !geohash_to_search.is_empty())
+            .map(|neighbor| self.distance_km(waypoint, &self.waypoints[neighbor.waypoint_index]));
+
+        if let Some(kth_km) = kth_nearest {
+            for neighbor_index in
+                self.sample_candidates_within(waypoint.lat, waypoint.lon, kth_km, keep_cell)
+            {
+                if visited.insert(neighbor_index) && is_candidate(neighbor_index) {
+                    nearest_neighbors.push(Connection::new(
+                        neighbor_index,
+                        self.metric_distance(waypoint, &self.waypoints[neighbor_index]),
+                    ))
+                }
+            }
+
+            nearest_neighbors.sort();
+            nearest_neighbors.truncate(k);
+        }
+
+        nearest_neighbors
+    }
+
+    /// Calculates the smallest bounding box containing every waypoint, e.g. to fit a map
+    /// viewport to the dataset. The longitudes are treated as points around a circle, and
+    /// the box spans them the shorter way round: if the widest gap between neighboring
+    /// waypoints' longitudes contains the antimeridian, the box is an ordinary one, and
+    /// otherwise it crosses the antimeridian. A box crossing the antimeridian has a western
+    /// edge greater than its eastern edge, as accepted by `query_bbox`.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(Coord, Coord, Coord, Coord)>`: The `(min_lat, min_lon, max_lat, max_lon)` of
+    ///   the box, or `None` if the dataset is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(39.7684, -86.1581).unwrap();
+    /// dataset.add_new_waypoint(41.8781, -87.6298).unwrap();
+    /// assert_eq!(dataset.bounds(), Some((39.7684, -87.6298, 41.8781, -86.1581)));
+    ///
+    /// // Waypoints either side of the antimeridian give a box crossing it
+    /// let mut pacific = zpath::Dataset::new();
+    /// pacific.add_new_waypoint(-17.7134, 178.065).unwrap();
+    /// pacific.add_new_waypoint(-13.759, -172.1046).unwrap();
+    /// pacific.add_new_waypoint(-21.1789, -175.1982).unwrap();
+    ///
+    /// let (min_lat, min_lon, max_lat, max_lon) = pacific.bounds().unwrap();
+    /// assert_eq!((min_lon, max_lon), (178.065, -172.1046));
+    /// assert_eq!(pacific.query_bbox(min_lat, min_lon, max_lat, max_lon).len(), 3);
+    /// ```
+    pub fn bounds(&self) -> Option<(Coord, Coord, Coord, Coord)> {
+        let first = self.waypoints.first()?;
+        let (mut min_lat, mut max_lat) = (first.lat, first.lat);
+        for waypoint in &self.waypoints {
+            min_lat = min_lat.min(waypoint.lat);
+            max_lat = max_lat.max(waypoint.lat);
+        }
+
+        let mut longitudes: Vec<Coord> = self.waypoints.iter().map(|w| w.lon).collect();
+        longitudes.sort_by(|a, b| a.total_cmp(b));
+        let (west, east) = (longitudes[0], longitudes[longitudes.len() - 1]);
+
+        // The gap across the antimeridian, from the easternmost longitude round to the
+        // westernmost, against the widest gap between neighboring longitudes
+        let wrap_gap = west + 360.0 - east;
+        let widest = longitudes
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0], pair[0], pair[1]))
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+
+        match widest {
+            Some((gap, before, after)) if gap > wrap_gap => Some((min_lat, after, max_lat, before)),
+            _ => Some((min_lat, west, max_lat, east)),
+        }
+    }
+
+    /// Finds all waypoints within the dataset that lie inside a bounding box. The box is
+    /// covered by a small set of geohash cells, starting from the cell shared by both of
+    /// its corners and refining into smaller cells while only a handful are needed. Each
+    /// cell is queried from the geohash index and the candidates are then filtered by
+    /// their exact coordinates.
+    ///
+    /// A box whose western edge is greater than its eastern edge is taken to cross the
+    /// antimeridian, and covers the longitudes from `min_lon` east to 180° and from -180°
+    /// east to `max_lon`.
+    ///
+    /// # Parameters
+    ///
+    /// - `min_lat`: The southern edge of the bounding box.
+    /// - `min_lon`: The western edge of the bounding box.
+    /// - `max_lat`: The northern edge of the bounding box.
+    /// - `max_lon`: The eastern edge of the bounding box.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the active waypoints inside the box (edges inclusive),
+    ///   sorted in ascending order. Empty if none, or if `min_lat` is greater than `max_lat`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let indianapolis = dataset.add_new_waypoint(39.7684, -86.1581).unwrap();
+    /// let chicago = dataset.add_new_waypoint(41.8781, -87.6298).unwrap();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// let fiji = dataset.add_new_waypoint(-17.7134, 178.065).unwrap();
+    /// let samoa = dataset.add_new_waypoint(-13.759, -172.1046).unwrap();
+    ///
+    /// let midwest = dataset.query_bbox(36.0, -92.0, 43.0, -84.0);
+    /// assert_eq!(midwest, vec![indianapolis, chicago]);
+    ///
+    /// // A box spanning the antimeridian in the South Pacific
+    /// let pacific = dataset.query_bbox(-25.0, 170.0, -10.0, -170.0);
+    /// assert_eq!(pacific, vec![fiji, samoa]);
+    /// ```
+    pub fn query_bbox(
+        &self,
+        min_lat: Coord,
+        min_lon: Coord,
+        max_lat: Coord,
+        max_lon: Coord,
+    ) -> Vec<usize> {
+        let mut found_waypoints =
+            self.sample_bbox(min_lat, min_lon, max_lat, max_lon, &mut || true);
+        found_waypoints.retain(|&i| self.waypoints[i].active);
+        found_waypoints
+    }
+
+    /// Finds the waypoints inside a bounding box in the same manner as `query_bbox`, but
+    /// only searching the geohash cells covering the box for which `keep_cell` returns
+    /// `true`.
+    ///
+    /// # Parameters
+    ///
+    /// - `min_lat`: The southern edge of the bounding box.
+    /// - `min_lon`: The western edge of the bounding box.
+    /// - `max_lat`: The northern edge of the bounding box.
+    /// - `max_lon`: The eastern edge of the bounding box.
+    /// - `keep_cell`: Called once for each covering cell, returning whether to search it.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints found, sorted in ascending order.
+    fn sample_bbox(
+        &self,
+        min_lat: Coord,
+        min_lon: Coord,
+        max_lat: Coord,
+        max_lon: Coord,
+        keep_cell: &mut dyn FnMut() -> bool,
+    ) -> Vec<usize> {
+        const MAX_COVERING_CELLS: usize = 32;
+
+        if min_lat > max_lat || min_lon > 180.0 || max_lon < -180.0 {
+            return Vec::new();
+        }
+
+        // Split a box that wraps around the antimeridian into the two halves either side of it
+        if min_lon > max_lon {
+            let mut found_waypoints = self.sample_bbox(min_lat, min_lon, max_lat, 180.0, keep_cell);
+            found_waypoints.extend(self.sample_bbox(min_lat, -180.0, max_lat, max_lon, keep_cell));
+            found_waypoints.sort_unstable();
+            found_waypoints.dedup();
+            return found_waypoints;
+        }
+
+        // Both corners falling within the same cell means the entire box does too
+        let southwest = geohash::encode_unchecked(min_lat, min_lon, self.precision);
+        let northeast = geohash::encode_unchecked(max_lat, max_lon, self.precision);
+        let mut precision = southwest
+            .chars()
+            .zip(northeast.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut cells = vec![String::from(&southwest[0..precision])];
+
+        // Trade the single large cell for several smaller ones while it remains cheap to do so
+        while precision < self.precision {
+            let finer_cells =
+                geohash::get_covering_cells(min_lat, min_lon, max_lat, max_lon, precision + 1);
+
+            if finer_cells.len() > MAX_COVERING_CELLS {
+                break;
+            }
+
+            cells = finer_cells;
+            precision += 1;
+        }
+
+        let mut found_waypoints = Vec::new();
+
+        for cell in cells.iter().filter(|_| keep_cell()) {
+            for index in self.search_geohash(cell) {
+                let waypoint = &self.waypoints[index];
+
+                if waypoint.lat >= min_lat
+                    && waypoint.lat <= max_lat
+                    && waypoint.lon >= min_lon
+                    && waypoint.lon <= max_lon
+                {
+                    found_waypoints.push(index);
+                }
+            }
+        }
+
+        found_waypoints.sort_unstable();
+        found_waypoints
+    }
+
+    /// Iterates over the waypoints in the dataset along with their indices.
+    ///
+    /// # Returns
+    ///
+    /// - `impl Iterator<Item = (usize, &Waypoint)>`: An iterator over each waypoint and its
+    ///   index, in index order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    ///
+    /// let southern: Vec<usize> = dataset
+    ///     .iter()
+    ///     .filter(|(_, waypoint)| waypoint.lat < 0.0)
+    ///     .map(|(index, _)| index)
+    ///     .collect();
+    /// assert_eq!(southern, vec![1]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Waypoint)> {
+        self.waypoints.iter().enumerate()
+    }
+
+    /// Iterates over the waypoints inside a bounding box along with their indices, using
+    /// `query_bbox` to find them with the geohash index.
+    ///
+    /// # Parameters
+    ///
+    /// - `min_lat`: The southern edge of the bounding box.
+    /// - `min_lon`: The western edge of the bounding box.
+    /// - `max_lat`: The northern edge of the bounding box.
+    /// - `max_lon`: The eastern edge of the bounding box.
+    ///
+    /// # Returns
+    ///
+    /// - `impl Iterator<Item = (usize, &Waypoint)>`: An iterator over each waypoint inside the
+    ///   box (edges inclusive) and its index, in index order. As with `query_bbox`, a box
+    ///   with `min_lon` greater than `max_lon` wraps around the antimeridian.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// dataset.add_new_waypoint(34.0522, -118.2437).unwrap();
+    /// dataset.add_new_waypoint(-33.8688, 151.2093).unwrap();
+    ///
+    /// let labels: Vec<&str> = dataset
+    ///     .iter_in_bbox(30.0, -125.0, 40.0, -115.0)
+    ///     .map(|(_, waypoint)| waypoint.label.as_str())
+    ///     .collect();
+    /// assert_eq!(labels, vec!["A", "B"]);
+    /// ```
+    pub fn iter_in_bbox(
+        &self,
+        min_lat: Coord,
+        min_lon: Coord,
+        max_lat: Coord,
+        max_lon: Coord,
+    ) -> impl Iterator<Item = (usize, &Waypoint)> {
+        self.query_bbox(min_lat, min_lon, max_lat, max_lon)
+            .into_iter()
+            .map(|index| (index, &self.waypoints[index]))
+    }
+
+    /// Finds the indices of all waypoints within the smallest bounding box that contains a
+    /// circle around the given coordinates, using `query_bbox`. Every waypoint within the
+    /// circle is included, along with some outside of it near the box's corners. Boxes
+    /// reaching a pole span all longitudes, and boxes crossing the antimeridian wrap.
+    ///
+    /// # Parameters
+    ///
+    /// - `lat`: The latitude of the circle's center.
+    /// - `lon`: The longitude of the circle's center.
+    /// - `radius_km`: The radius of the circle in kilometers.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the candidate waypoints.
+    fn get_candidates_within(&self, lat: Coord, lon: Coord, radius_km: f32) -> Vec<usize> {
+        self.sample_candidates_within(lat, lon, radius_km, &mut || true)
+    }
+
+    /// Finds the candidate waypoints around the given coordinates in the same manner as
+    /// `get_candidates_within`, but only searching the geohash cells for which `keep_cell`
+    /// returns `true` (see `sample_bbox`).
+    ///
+    /// # Parameters
+    ///
+    /// - `lat`: The latitude of the circle's center.
+    /// - `lon`: The longitude of the circle's center.
+    /// - `radius_km`: The radius of the circle in kilometers.
+    /// - `keep_cell`: Called once for each covering cell, returning whether to search it.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the candidate waypoints.
+    fn sample_candidates_within(
+        &self,
+        lat: Coord,
+        lon: Coord,
+        radius_km: f32,
+        keep_cell: &mut dyn FnMut() -> bool,
+    ) -> Vec<usize> {
+        let angular_radius = Coord::from(radius_km / self.earth_radius);
+        let lat_delta = angular_radius.to_degrees();
+
+        if lat + lat_delta >= 90.0 || lat - lat_delta <= -90.0 {
+            return self.sample_bbox(lat - lat_delta, -180.0, lat + lat_delta, 180.0, keep_cell);
+        }
+
+        // The widest longitude reached by a circle on a sphere
+        let lon_delta = (angular_radius.sin() / lat.to_radians().cos())
+            .min(1.0)
+            .asin()
+            .to_degrees();
+        let (min_lat, max_lat) = (lat - lat_delta, lat + lat_delta);
+        let (min_lon, max_lon) = (lon - lon_delta, lon + lon_delta);
+
+        // Boxes crossing the antimeridian are expressed as wrapping boxes
+        if min_lon < -180.0 {
+            self.sample_bbox(min_lat, min_lon + 360.0, max_lat, max_lon, keep_cell)
+        } else if max_lon > 180.0 {
+            self.sample_bbox(min_lat, min_lon, max_lat, max_lon - 360.0, keep_cell)
+        } else {
+            self.sample_bbox(min_lat, min_lon, max_lat, max_lon, keep_cell)
+        }
+    }
+
+    /// Finds all waypoints within a given distance of a specified waypoint, using the
+    /// geohash index to only measure the distance to nearby waypoints.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint at the center of the search.
+    /// - `radius_km`: The maximum distance from `target` in kilometers (inclusive).
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing connections to every waypoint within the
+    ///   radius, sorted by distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let home = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let near = dataset.add_new_waypoint(0.0, 0.5).unwrap();
+    /// dataset.add_new_waypoint(0.0, 5.0).unwrap();
+    ///
+    /// let nearby = dataset.get_within_radius(&dataset.waypoints[home], 100.0, Some(home));
+    /// assert_eq!(nearby.len(), 1);
+    /// assert_eq!(nearby[0].waypoint_index, near);
+    ///
+    /// // A copy of a waypoint is treated just like the original
+    /// let copy = dataset.waypoints[home].clone();
+    /// assert_eq!(dataset.get_within_radius(&copy, 100.0, Some(home)), nearby);
+    /// assert_eq!(dataset.get_within_radius(&copy, 100.0, None)[0].waypoint_index, home);
+    /// ```
+    pub fn get_within_radius(
+        &self,
+        target: &Waypoint,
+        radius_km: f32,
+        exclude: Option<usize>,
+    ) -> Vec<Connection> {
+        self.get_within_annulus(target, 0.0, radius_km, exclude)
+    }
+
+    /// Finds all waypoints whose distance from a specified waypoint lies between an inner
+    /// and an outer radius, using the geohash index to only measure the distance to nearby
+    /// waypoints. Useful for tiered proximity searches that exclude waypoints which are
+    /// too close.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint at the center of the search.
+    /// - `inner_km`: The minimum distance from `target` in kilometers (inclusive).
+    /// - `outer_km`: The maximum distance from `target` in kilometers (inclusive).
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Connection>`: A vector containing connections to every waypoint between the two
+    ///   radii, sorted by distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let home = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.05).unwrap();
+    /// let mid = dataset.add_new_waypoint(0.0, 0.3).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    ///
+    /// let ring = dataset.get_within_annulus(&dataset.waypoints[home], 10.0, 50.0, Some(home));
+    /// assert_eq!(ring.len(), 1);
+    /// assert_eq!(ring[0].waypoint_index, mid);
+    /// ```
+    pub fn get_within_annulus(
+        &self,
+        target: &Waypoint,
+        inner_km: f32,
+        outer_km: f32,
+        exclude: Option<usize>,
+    ) -> Vec<Connection> {
+        let mut found: Vec<Connection> = self
+            .get_candidates_within(target.lat, target.lon, outer_km)
+            .into_iter()
+            .filter(|&i| exclude != Some(i) && self.waypoints[i].active)
+            .map(|i| Connection::new(i, self.distance_km(target, &self.waypoints[i])))
+            .filter(|c| c.distance >= inner_km && c.distance <= outer_km)
+            .collect();
+
+        found.sort_by(|a, b| {
+            a.distance
+                .partial_cmp(&b.distance)
+                .unwrap_or(Ordering::Equal)
+        });
+        found
+    }
+
+    /// Finds all waypoints within a given distance of a specified waypoint that also lie
+    /// within a cone of bearings either side of a heading, e.g. for the field of view of a
+    /// directional sensor. The waypoints within the radius are found with
+    /// `get_within_radius`, then kept if the bearing to them from `target` (see
+    /// `Waypoint::get_bearing_to`) is within `half_angle_deg` of `heading_deg`. The cone may
+    /// span North, e.g. a heading of `350.0` with a half angle of `20.0` covers bearings from
+    /// `330.0` round to `10.0`.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: A reference to the waypoint at the point of the cone.
+    /// - `radius_km`: The maximum distance from `target` in kilometers (inclusive).
+    /// - `heading_deg`: The bearing along the middle of the cone, in degrees clockwise from
+    ///   North.
+    /// - `half_angle_deg`: The greatest difference from `heading_deg` in degrees (inclusive).
+    ///   A half angle of `180.0` or more covers every bearing.
+    /// - `exclude`: The index of a waypoint to leave out of the results, usually that of
+    ///   `target` itself when it's one of the dataset's waypoints, or `None` to leave none out.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints within the sector, sorted by distance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let home = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let north = dataset.add_new_waypoint(0.5, 0.0).unwrap();
+    /// let north_west = dataset.add_new_waypoint(0.5, -0.1).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.5).unwrap();
+    /// dataset.add_new_waypoint(5.0, 0.0).unwrap();
+    ///
+    /// let ahead = dataset.query_sector(&dataset.waypoints[home], 100.0, 350.0, 20.0, Some(home));
+    /// assert_eq!(ahead, vec![north, north_west]);
+    /// ```
+    pub fn query_sector(
+        &self,
+        target: &Waypoint,
+        radius_km: f32,
+        heading_deg: f32,
+        half_angle_deg: f32,
+        exclude: Option<usize>,
+    ) -> Vec<usize> {
+        self.get_within_radius(target, radius_km, exclude)
+            .into_iter()
+            .filter(|connection| {
+                let bearing = target.get_bearing_to(&self.waypoints[connection.waypoint_index]);
+
+                // The angle between the bearing and the heading, going whichever way is shorter
+                let difference = (bearing - heading_deg).rem_euclid(360.0);
+                let difference = if difference > 180.0 {
+                    360.0 - difference
+                } else {
+                    difference
+                };
+
+                difference <= half_angle_deg
+            })
+            .map(|connection| connection.waypoint_index)
+            .collect()
+    }
+
+    /// Finds every pair of waypoints within a given distance of each other, such as
+    /// near-duplicate points to be merged. Rather than comparing every pair of waypoints,
+    /// each waypoint is only compared against the candidates found near it with the
+    /// geohash index.
+    ///
+    /// # Parameters
+    ///
+    /// - `threshold_km`: The maximum distance between the waypoints of a pair in kilometers (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(usize, usize, f32)>`: Each pair of active waypoints as `(a, b, distance)`, with
+    ///   `a < b` so that every pair appears only once, sorted by `a` and then `b`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    /// let b = dataset.add_new_waypoint(37.7750, -122.4195).unwrap();
+    /// dataset.add_new_waypoint(34.0522, -118.2437).unwrap();
+    /// let c = dataset.add_new_waypoint(37.7749, -122.4194).unwrap();
+    ///
+    /// let pairs: Vec<(usize, usize)> = dataset
+    ///     .close_pairs(0.05)
+    ///     .into_iter()
+    ///     .map(|(a, b, _)| (a, b))
+    ///     .collect();
+    /// assert_eq!(pairs, vec![(a, b), (a, c), (b, c)]);
+    /// ```
+    pub fn close_pairs(&self, threshold_km: f32) -> Vec<(usize, usize, f32)> {
+        let mut pairs = Vec::new();
+
+        for (a, waypoint) in self.iter().filter(|(_, waypoint)| waypoint.active) {
+            let mut candidates =
+                self.get_candidates_within(waypoint.lat, waypoint.lon, threshold_km);
+            candidates.sort_unstable();
+            candidates.dedup();
+
+            for b in candidates
+                .into_iter()
+                .filter(|&b| b > a && self.waypoints[b].active)
+            {
+                let distance = self.distance_km(waypoint, &self.waypoints[b]);
+                if distance <= threshold_km {
+                    pairs.push((a, b, distance));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// Calculates the convex hull of the waypoints in the dataset using Andrew's monotone
+    /// chain algorithm, treating longitude and latitude as planar x and y coordinates.
+    ///
+    /// As the hull is planar rather than spherical, it is only approximate for datasets
+    /// covering large areas, and is meaningless for datasets that span the antimeridian
+    /// (where the hull wraps the long way around the map) or surround a pole.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints on the hull in counter-clockwise order,
+    ///   starting from the westernmost (then southernmost) waypoint. Waypoints lying along an
+    ///   edge of the hull are not included, and of waypoints sharing the same coordinates only
+    ///   one is included. Datasets with fewer than three distinct locations return each of them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let south_west = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let south_east = dataset.add_new_waypoint(0.0, 10.0).unwrap();
+    /// let north_east = dataset.add_new_waypoint(10.0, 10.0).unwrap();
+    /// let north_west = dataset.add_new_waypoint(10.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(5.0, 5.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 5.0).unwrap();
+    ///
+    /// assert_eq!(dataset.convex_hull(), vec![south_west, south_east, north_east, north_west]);
+    /// ```
+    pub fn convex_hull(&self) -> Vec<usize> {
+        let mut points: Vec<usize> = (0..self.waypoints.len()).collect();
+        points.sort_by(|&a, &b| {
+            let (a, b) = (&self.waypoints[a], &self.waypoints[b]);
+            a.lon.total_cmp(&b.lon).then(a.lat.total_cmp(&b.lat))
+        });
+        points.dedup_by(|a, b| {
+            let (a, b) = (&self.waypoints[*a], &self.waypoints[*b]);
+            a.lon == b.lon && a.lat == b.lat
+        });
+
+        if points.len() < 3 {
+            return points;
+        }
+
+        // Positive if o -> a -> b turns counter-clockwise, negative if clockwise, and zero if straight
+        let cross = |o: usize, a: usize, b: usize| {
+            let (o, a, b) = (&self.waypoints[o], &self.waypoints[a], &self.waypoints[b]);
+            (a.lon - o.lon) * (b.lat - o.lat) - (a.lat - o.lat) * (b.lon - o.lon)
+        };
+
+        // Build the lower hull from West to East
+        let mut hull: Vec<usize> = Vec::with_capacity(points.len() + 1);
+        for &point in &points {
+            while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+
+        // Then build the upper hull from East to West, continuing on from the lower hull
+        let lower_length = hull.len() + 1;
+        for &point in points.iter().rev().skip(1) {
+            while hull.len() >= lower_length
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+
+        // The upper hull finishes back at the westernmost waypoint, which is already first
+        hull.pop();
+        hull
+    }
+
+    /// Calculates the area of the Earth's surface enclosed by a polygon of waypoints, e.g. to
+    /// report the area covered by the dataset's `convex_hull`. The edges of the polygon are
+    /// great-circle segments. The polygon is split into triangles fanning out from its first
+    /// vertex, and the area of each is found from its spherical excess using L'Huilier's
+    /// formula. Each triangle's area is signed by its winding, so that concave polygons are
+    /// measured correctly, and the polygon may be given in either winding order.
+    ///
+    /// # Parameters
+    ///
+    /// - `indices`: The indices of the waypoints at the polygon's vertices, in order. The
+    ///   last vertex joins back to the first. The polygon shouldn't cross itself.
+    ///
+    /// # Returns
+    ///
+    /// - `f64`: The enclosed area in square kilometers, using `EARTH_RADIUS`. `0.0` if there are
+    ///   fewer than three vertices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.5, 0.5).unwrap();
+    ///
+    /// // A square of one degree at the equator covers about 12,400 square kilometers
+    /// let area = dataset.spherical_polygon_area(&dataset.convex_hull());
+    /// assert!((area - 12_391.0).abs() < 10.0);
+    ///
+    /// // Winding the other way gives the same area
+    /// let reversed = dataset.spherical_polygon_area(&[3, 2, 1, 0]);
+    /// assert!((reversed - area).abs() < 1e-6);
+    /// ```
+    pub fn spherical_polygon_area(&self, indices: &[usize]) -> f64 {
+        if indices.len() < 3 {
+            return 0.0;
+        }
+
+        // Convert each vertex to a unit vector from the center of the Earth. The conversion
+        // to f64 is only needed when coordinates are f32
+        #[allow(clippy::useless_conversion)]
+        let vertices: Vec<[f64; 3]> = indices
+            .iter()
+            .map(|&i| {
+                let lat = f64::from(self.waypoints[i].lat).to_radians();
+                let lon = f64::from(self.waypoints[i].lon).to_radians();
+                [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+            })
+            .collect();
+
+        let angle_between = |a: &[f64; 3], b: &[f64; 3]| {
+            let dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+            dot.clamp(-1.0, 1.0).acos()
+        };
+
+        let mut excess = 0.0;
+        for pair in vertices[1..].windows(2) {
+            let (a, b, c) = (&vertices[0], &pair[0], &pair[1]);
+
+            // L'Huilier's formula for the spherical excess of the triangle
+            let (side_a, side_b, side_c) = (
+                angle_between(b, c),
+                angle_between(a, c),
+                angle_between(a, b),
+            );
+            let s = (side_a + side_b + side_c) / 2.0;
+            let product = (s / 2.0).tan()
+                * ((s - side_a) / 2.0).tan()
+                * ((s - side_b) / 2.0).tan()
+                * ((s - side_c) / 2.0).tan();
+            let triangle_excess = 4.0 * product.max(0.0).sqrt().atan();
+
+            // Triangles wound against the polygon's overall winding are subtracted
+            let orientation = a[0] * (b[1] * c[2] - b[2] * c[1])
+                - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0]);
+            excess += triangle_excess.copysign(orientation);
+        }
+
+        let radius = f64::from(self.earth_radius);
+        excess.abs() * radius * radius
+    }
+
+    /// Finds the two waypoints farthest apart, e.g. to choose a map zoom that fits the whole
+    /// dataset. Rather than comparing every pair of waypoints, the rotating calipers method
+    /// is applied to the `convex_hull` to find the pairs of hull waypoints on opposite sides
+    /// of it, and the great-circle distance is measured between each such pair, which takes
+    /// O(n log n) time overall.
+    ///
+    /// This is the greatest straight-line distance between waypoints, not the longest route
+    /// through the connections. As the hull is planar, the result is approximate for
+    /// datasets covering large areas, and unreliable for those spanning the antimeridian.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(usize, usize, f32)>`: The indices of the two waypoints, with the lower
+    ///   index first, and the distance between them in kilometers. `None` if the dataset has
+    ///   fewer than two waypoints.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let west = dataset.add_new_waypoint(0.0, -20.0).unwrap();
+    /// dataset.add_new_waypoint(5.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(-5.0, 5.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let east = dataset.add_new_waypoint(1.0, 15.0).unwrap();
+    ///
+    /// let (a, b, distance) = dataset.farthest_pair().unwrap();
+    /// assert_eq!((a, b), (west, east));
+    /// assert_eq!(distance, dataset.waypoints[west].get_distance_to(&dataset.waypoints[east]));
+    /// ```
+    pub fn farthest_pair(&self) -> Option<(usize, usize, f32)> {
+        let hull = self.convex_hull();
+
+        let candidates: Vec<(usize, usize)> = match hull.len() {
+            // Every waypoint shares the same location
+            0 | 1 if self.waypoints.len() >= 2 => vec![(0, 1)],
+            0 | 1 => return None,
+            2 => vec![(hull[0], hull[1])],
+            h => {
+                let doubled_area = |a: usize, b: usize, c: usize| {
+                    let (a, b, c) = (&self.waypoints[a], &self.waypoints[b], &self.waypoints[c]);
+                    ((b.lon - a.lon) * (c.lat - a.lat) - (b.lat - a.lat) * (c.lon - a.lon)).abs()
+                };
+
+                // For each edge of the hull, advance to the vertex farthest from it; that
+                // vertex is on the opposite side of the hull from both ends of the edge
+                let mut pairs = Vec::with_capacity(2 * h);
+                let mut j = 1;
+                for i in 0..h {
+                    let next = (i + 1) % h;
+                    while doubled_area(hull[i], hull[next], hull[(j + 1) % h])
+                        > doubled_area(hull[i], hull[next], hull[j])
+                    {
+                        j = (j + 1) % h;
+                    }
+                    pairs.push((hull[i], hull[j]));
+                    pairs.push((hull[next], hull[j]));
+                }
+                pairs
+            }
+        };
+
+        candidates
+            .into_iter()
+            .map(|(a, b)| {
+                let distance = self.distance_km(&self.waypoints[a], &self.waypoints[b]);
+                (a.min(b), a.max(b), distance)
+            })
+            .max_by(|x, y| x.2.total_cmp(&y.2))
+    }
+
+    /// Finds the geographic centroid of a set of waypoints. Each waypoint is converted to a
+    /// 3D unit vector, the vectors are averaged, and the result is projected back onto the
+    /// sphere. Unlike averaging latitudes and longitudes directly, this gives a sensible
+    /// result for groups that straddle the antimeridian or surround a pole.
+    ///
+    /// # Parameters
+    ///
+    /// - `indices`: The indices of the waypoints to find the centroid of.
+    ///
+    /// # Returns
+    ///
+    /// - `(Coord, Coord)`: The `(lat, lon)` of the centroid. If no indices are given, or the
+    ///   waypoints are spread evenly enough that their vectors cancel out (e.g. two antipodal
+    ///   waypoints), there is no meaningful centroid and `(0.0, 0.0)` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let east = dataset.add_new_waypoint(10.0, 179.0).unwrap();
+    /// let west = dataset.add_new_waypoint(-10.0, -179.0).unwrap();
+    ///
+    /// // Naive averaging would place this near (0, 0), on the opposite side of the Earth
+    /// let (lat, lon) = dataset.centroid(&[east, west]);
+    /// assert!(lat.abs() < 1e-3);
+    /// assert!((lon.abs() - 180.0).abs() < 1e-3);
+    ///
+    /// // The centroid can be used to place a representative waypoint for the group
+    /// let center = dataset.add_new_waypoint(lat, lon).unwrap();
+    /// assert_eq!(dataset.waypoints[center].label, "C");
+    /// ```
+    pub fn centroid(&self, indices: &[usize]) -> (Coord, Coord) {
+        let (mut x, mut y, mut z): (Coord, Coord, Coord) = (0.0, 0.0, 0.0);
+
+        for &index in indices {
+            let [vx, vy, vz] = to_unit_vector(self.waypoints[index].lat, self.waypoints[index].lon);
+
+            x += vx;
+            y += vy;
+            z += vz;
+        }
+
+        // Points spread evenly about the sphere sum to (roughly) the zero vector
+        if x.hypot(y).hypot(z) < Coord::EPSILON {
+            return (0.0, 0.0);
+        }
+
+        from_vector(&[x, y, z])
+    }
+
+    /// Finds the waypoint nearest to the geographic center of the dataset, i.e. the
+    /// spherical centroid of every waypoint (see `centroid`). Useful as a representative
+    /// starting point or hub.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<usize>`: The index of the waypoint nearest to the center, or `None` if the
+    ///   dataset is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, -10.0).unwrap();
+    /// dataset.add_new_waypoint(10.0, 0.0).unwrap();
+    /// let middle = dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 10.0).unwrap();
+    /// dataset.add_new_waypoint(-10.0, 0.0).unwrap();
+    ///
+    /// assert_eq!(dataset.most_central_waypoint(), Some(middle));
+    /// assert_eq!(zpath::Dataset::new().most_central_waypoint(), None);
+    /// ```
+    pub fn most_central_waypoint(&self) -> Option<usize> {
+        let indices: Vec<usize> = (0..self.waypoints.len()).collect();
+        let (lat, lon) = self.centroid(&indices);
+
+        self.nearest(lat, lon, 1).first().copied()
+    }
+
+    /// Partitions the waypoints in the dataset into `k` geographic clusters using k-means.
+    /// The initial cluster centers are `k` distinct waypoints chosen at random; each
+    /// iteration then assigns every waypoint to its nearest center by great-circle distance
+    /// and moves each center to the spherical centroid of its cluster (see `centroid`).
+    /// Stops early once an iteration leaves every assignment unchanged.
+    ///
+    /// # Parameters
+    ///
+    /// - `k`: The number of clusters. Clamped to the number of waypoints in the dataset.
+    /// - `iterations`: The maximum number of times to update the cluster centers.
+    /// - `seed`: The seed used to choose the initial centers; the same seed always produces
+    ///   the same clusters for the same dataset.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The cluster (from `0` to `k - 1`) of each waypoint, in the same order as
+    ///   `waypoints`. Empty if `k` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(40.0, -100.0).unwrap();
+    /// dataset.add_new_waypoint(41.0, -101.0).unwrap();
+    /// dataset.add_new_waypoint(40.5, -99.0).unwrap();
+    /// dataset.add_new_waypoint(-30.0, 120.0).unwrap();
+    /// dataset.add_new_waypoint(-31.0, 121.0).unwrap();
+    ///
+    /// let clusters = dataset.kmeans(2, 10, 42);
+    /// assert_eq!(clusters.len(), 5);
+    /// assert!(clusters[0] == clusters[1] && clusters[1] == clusters[2]);
+    /// assert!(clusters[3] == clusters[4] && clusters[0] != clusters[3]);
+    /// ```
+    pub fn kmeans(&self, k: usize, iterations: usize, seed: u64) -> Vec<usize> {
+        let k = k.min(self.waypoints.len());
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // Choose k distinct waypoints as the initial centers with a partial Fisher-Yates shuffle
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        let mut candidates: Vec<usize> = (0..self.waypoints.len()).collect();
+        for i in 0..k {
+            let j = i + rng.next_u32() as usize % (candidates.len() - i);
+            candidates.swap(i, j);
+        }
+        let mut centers: Vec<Waypoint> = candidates[..k]
+            .iter()
+            .map(|&index| {
+                let waypoint = &self.waypoints[index];
+                Waypoint::new(waypoint.lat, waypoint.lon, String::new())
+            })
+            .collect();
+
+        let mut assignments = self.assign_to_nearest_center(&centers);
+
+        for _ in 0..iterations {
+            let mut members: Vec<Vec<usize>> = vec![Vec::new(); k];
+            for (index, &cluster) in assignments.iter().enumerate() {
+                members[cluster].push(index);
+            }
+
+            // A cluster left without any members keeps its previous center
+            for (center, members) in centers.iter_mut().zip(&members) {
+                if !members.is_empty() {
+                    let (lat, lon) = self.centroid(members);
+                    *center = Waypoint::new(lat, lon, String::new());
+                }
+            }
+
+            let new_assignments = self.assign_to_nearest_center(&centers);
+            if new_assignments == assignments {
+                break;
+            }
+            assignments = new_assignments;
+        }
+
+        assignments
+    }
+
+    /// Assigns each waypoint in the dataset to the nearest of the given cluster centers, as
+    /// used by `kmeans`. Ties are resolved in favor of the center listed first.
+    ///
+    /// # Parameters
+    ///
+    /// - `centers`: The cluster centers. Must not be empty.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The index of the nearest center for each waypoint.
+    fn assign_to_nearest_center(&self, centers: &[Waypoint]) -> Vec<usize> {
+        self.waypoints
+            .iter()
+            .map(|waypoint| {
+                let mut nearest = 0;
+                let mut nearest_distance = f32::INFINITY;
+
+                for (cluster, center) in centers.iter().enumerate() {
+                    let distance = waypoint.get_distance_to(center);
+                    if distance < nearest_distance {
+                        nearest = cluster;
+                        nearest_distance = distance;
+                    }
+                }
+
+                nearest
+            })
+            .collect()
+    }
+
+    /// Iterates through each waypoint in the dataset and assigns connections to it based on
+    /// K-nearest neighbors, calculated using the `get_knn_geohash` method. Populates the
+    /// `connections` field of each waypoint with the calculated connections. If the dataset
+    /// isn't `directed`, each connection is then matched with one in the opposite direction.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of nearest neighbors (K) to consider for each waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let k = 3;
+    /// dataset.assign_all_connections_geohash(k);
+    /// // All waypoints in dataset will now have 3 connections
+    /// ```
+    pub fn assign_all_connections_geohash(&mut self, amt: usize) {
+        self.requested_connections = amt;
+        let mut created = Vec::new();
+        for i in 0..self.waypoints.len() {
+            let connections = self.get_knn_geohash_by_index(i, amt);
+            created.extend(connections.iter().map(|connection| (i, connection.clone())));
+            self.waypoints[i].connections.extend(connections);
+        }
+
+        if !self.directed {
+            self.add_reverse_connections(&created);
+        }
+    }
+
+    /// Iterates through each waypoint in the dataset and assigns connections to its K-nearest
+    /// neighbors in the same manner as `assign_all_connections_geohash`, skipping any
+    /// neighbor whose connection would cross into an obstacle such as a lake or restricted
+    /// airspace. Routing over the resulting connections therefore never passes through an
+    /// obstacle. The search widens past the K-nearest neighbors as needed, so a waypoint is
+    /// only left with fewer than K connections when no more neighbors can be reached.
+    ///
+    /// A connection is blocked if its great-circle segment crosses an edge of an obstacle,
+    /// or if either of its waypoints lies inside an obstacle. The edges of each obstacle are
+    /// also great-circle segments, but whether a waypoint lies inside one is tested on a flat
+    /// plane of latitude and longitude. This approximation breaks down for obstacles near the
+    /// poles or crossing the antimeridian.
+    ///
+    /// # Parameters
+    ///
+    /// - `k`: The number of connections to assign to each waypoint.
+    /// - `obstacles`: The obstacles to avoid, each given as the `(lat, lon)` of its vertices
+    ///   in order. The last vertex joins back to the first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let west = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// let north = dataset.add_new_waypoint(2.0, 1.0).unwrap();
+    ///
+    /// // A lake lies directly between the two waypoints on the equator
+    /// let lake = vec![(-0.5, 0.5), (-0.5, 1.5), (0.5, 1.5), (0.5, 0.5)];
+    /// dataset.assign_connections_avoiding(1, &[lake]);
+    ///
+    /// assert_eq!(dataset.waypoints[west].connections[0].waypoint_index, north);
+    /// ```
+    pub fn assign_connections_avoiding(&mut self, k: usize, obstacles: &[Vec<(Coord, Coord)>]) {
+        self.requested_connections = k;
+        let obstacles: Vec<Vec<Waypoint>> = obstacles
+            .iter()
+            .map(|vertices| {
+                vertices
+                    .iter()
+                    .map(|&(lat, lon)| Waypoint::new(lat, lon, String::new()))
+                    .collect()
+            })
+            .collect();
+
+        let inside_obstacle = |waypoint: &Waypoint| {
+            obstacles
+                .iter()
+                .any(|obstacle| point_in_polygon(waypoint.lat, waypoint.lon, obstacle))
+        };
+        let crosses_obstacle = |a: &Waypoint, b: &Waypoint| {
+            obstacles.iter().any(|obstacle| {
+                (0..obstacle.len()).any(|i| {
+                    let (start, end) = (&obstacle[i], &obstacle[(i + 1) % obstacle.len()]);
+                    intersection(a, b, start, end).is_some()
+                })
+            })
+        };
+
+        let max_candidates = self.waypoints.len().saturating_sub(1);
+        let mut created = Vec::new();
+        for i in 0..self.waypoints.len() {
+            let waypoint = &self.waypoints[i];
+            if inside_obstacle(waypoint) {
+                continue;
+            }
+
+            // Widen the search until enough neighbors are reachable or none are left
+            let mut candidate_count = k.min(max_candidates);
+            let connections = loop {
+                let reachable: Vec<Connection> = self
+                    .get_knn_geohash_by_index(i, candidate_count)
+                    .into_iter()
+                    .filter(|candidate| {
+                        let neighbor = &self.waypoints[candidate.waypoint_index];
+                        !inside_obstacle(neighbor) && !crosses_obstacle(waypoint, neighbor)
+                    })
+                    .take(k)
+                    .collect();
+
+                if reachable.len() == k || candidate_count >= max_candidates {
+                    break reachable;
+                }
+                candidate_count = (candidate_count * 2).clamp(1, max_candidates);
+            };
+
+            created.extend(connections.iter().map(|connection| (i, connection.clone())));
+            self.waypoints[i].connections.extend(connections);
+        }
+
+        if !self.directed {
+            self.add_reverse_connections(&created);
+        }
+    }
+
+    /// Assigns connections to every waypoint in the same manner as
+    /// `assign_all_connections_geohash`, and measures how long the assignment took.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of nearest neighbors (K) to consider for each waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Duration`: The time taken to assign the connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let generation = dataset.generate_waypoints_timed(1000, 42);
+    /// let assignment = dataset.assign_all_connections_geohash_timed(5);
+    ///
+    /// println!("Generation: {:?}, assignment: {:?}", generation, assignment);
+    /// ```
+    pub fn assign_all_connections_geohash_timed(&mut self, amt: usize) -> Duration {
+        let start = Instant::now();
+        self.assign_all_connections_geohash(amt);
+        start.elapsed()
+    }
+
+    /// Iterates through each waypoint in the dataset and assigns connections to it in the
+    /// same manner as `assign_all_connections_geohash`, but only to those of its K-nearest
+    /// neighbors that lie within a maximum distance. Waypoints in sparse regions are left
+    /// with fewer connections, or none at all, rather than being connected to distant
+    /// waypoints.
+    ///
+    /// # Parameters
+    ///
+    /// - `k`: The number of nearest neighbors (K) to consider for each waypoint.
+    /// - `max_km`: The maximum length of a connection in kilometers (inclusive).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints that had no neighbors within `max_km`, and
+    ///   so were assigned no connections, in ascending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 0.5).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let remote = dataset.add_new_waypoint(45.0, 90.0).unwrap();
+    ///
+    /// let unconnected = dataset.assign_all_connections_within(3, 150.0);
+    /// assert_eq!(unconnected, vec![remote]);
+    /// assert_eq!(dataset.waypoints[0].connections.len(), 2);
+    /// ```
+    pub fn assign_all_connections_within(&mut self, k: usize, max_km: f32) -> Vec<usize> {
+        self.requested_connections = k;
+        let mut unconnected = Vec::new();
+        let mut created = Vec::new();
+
+        for i in 0..self.waypoints.len() {
+            let connections: Vec<Connection> = self
+                .get_knn_geohash_by_index(i, k)
+                .into_iter()
+                .filter(|connection| connection.distance <= max_km)
+                .collect();
+
+            if connections.is_empty() {
+                unconnected.push(i);
+            }
+            created.extend(connections.iter().map(|connection| (i, connection.clone())));
+            self.waypoints[i].connections.extend(connections);
+        }
+
+        if !self.directed {
+            self.add_reverse_connections(&created);
+        }
+
+        unconnected
+    }
+
+    /// Iterates through each waypoint in the dataset and assigns connections to it based on
+    /// K-nearest neighbors, calculated using the `get_knn_naive` method. Populates the
+    /// `connections` field of each waypoint with the calculated connections. If the dataset
+    /// isn't `directed`, each connection is then matched with one in the opposite direction.
+    ///
+    /// # Parameters
+    ///
+    /// - `amt`: The number of nearest neighbors (K) to consider for each waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10);
+    ///
+    /// let k = 3;
+    /// dataset.assign_all_connections_naive(k);
+    /// // All waypoints in dataset will now have 3 connections
+    ///
+    /// // In an undirected dataset, waypoints also connect back to those connecting to them
+    /// let mut undirected = zpath::Dataset::new();
+    /// undirected.directed = false;
+    /// undirected.generate_waypoints(10);
+    /// undirected.assign_all_connections_naive(k);
+    /// assert!(undirected.graph_metrics().symmetric);
+    /// assert!(undirected.waypoints.iter().all(|waypoint| waypoint.connections.len() >= k));
+    ///
+    /// // Connections added beforehand are left as they were
+    /// let mut manual = zpath::Dataset::new();
+    /// manual.directed = false;
+    /// for lon in [0.0, 1.0, 2.0] {
+    ///     manual.add_new_waypoint(0.0, lon).unwrap();
+    /// }
+    /// manual.waypoints[0].connections.push(zpath::Connection::new(2, 999.0));
+    /// manual.assign_all_connections_naive(1);
+    /// assert_eq!(manual.waypoints[0].connections[0].waypoint_index, 2);
+    /// assert_eq!(manual.waypoints[0].connections[0].distance, 999.0);
+    /// ```
+    pub fn assign_all_connections_naive(&mut self, amt: usize) {
+        self.requested_connections = amt;
+        let mut created = Vec::new();
+        for i in 0..self.waypoints.len() {
+            let connections = self.get_knn_naive_by_index(i, amt);
+            created.extend(connections.iter().map(|connection| (i, connection.clone())));
+            self.waypoints[i].connections.extend(connections);
+        }
+
+        if !self.directed {
+            self.add_reverse_connections(&created);
+        }
+    }
+
+    /// Removes every connection from every waypoint in the dataset, leaving the waypoints
+    /// themselves and the geohash index untouched. As the `assign_all_connections_*` methods
+    /// add to any existing connections, this allows connections to be reassigned (e.g. with
+    /// a different number of neighbors) without regenerating the dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    ///
+    /// for k in 1..=5 {
+    ///     dataset.clear_connections();
+    ///     dataset.assign_all_connections_geohash(k);
+    ///     assert!(dataset.waypoints.iter().all(|waypoint| waypoint.connections.len() == k));
+    /// }
+    ///
+    /// dataset.clear_connections();
+    /// assert!(dataset.waypoints.iter().all(|waypoint| waypoint.connections.is_empty()));
+    /// ```
+    pub fn clear_connections(&mut self) {
+        for waypoint in &mut self.waypoints {
+            waypoint.connections.clear();
+        }
+        self.requested_connections = 0;
+    }
+
+    /// Finds connections which don't lead anywhere: self-loops, where a waypoint is connected
+    /// to itself, and connections with a distance of zero, such as between coincident
+    /// waypoints. These can confuse routing, and can be left behind by merging datasets or
+    /// editing connections by hand. The `assign_all_connections_*` methods never create
+    /// self-loops, so finding one after assignment alone indicates a bug.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(usize, usize)>`: The indices of the waypoint each degenerate connection leaves
+    ///   from and the waypoint it leads to, ordered by the waypoint it leaves from.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let c = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    /// dataset.waypoints[c].connections.push(zpath::Connection::new(c, 0.0));
+    ///
+    /// // The coincident waypoints are connected to each other with a distance of zero
+    /// assert_eq!(dataset.find_degenerate_edges(), vec![(a, b), (b, a), (c, c)]);
+    ///
+    /// assert_eq!(dataset.remove_degenerate_edges(), 3);
+    /// assert!(dataset.find_degenerate_edges().is_empty());
+    /// assert_eq!(dataset.waypoints[c].connections.len(), 1);
+    /// ```
+    pub fn find_degenerate_edges(&self) -> Vec<(usize, usize)> {
+        self.waypoints
+            .iter()
+            .enumerate()
+            .flat_map(|(i, waypoint)| {
+                waypoint
+                    .connections
+                    .iter()
+                    .filter(move |connection| Dataset::is_degenerate(i, connection))
+                    .map(move |connection| (i, connection.waypoint_index))
+            })
+            .collect()
+    }
+
+    /// Removes every connection reported by `find_degenerate_edges`.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The number of connections removed.
+    pub fn remove_degenerate_edges(&mut self) -> usize {
+        let mut removed = 0;
+        for (i, waypoint) in self.waypoints.iter_mut().enumerate() {
+            let before = waypoint.connections.len();
+            waypoint
+                .connections
+                .retain(|connection| !Dataset::is_degenerate(i, connection));
+            removed += before - waypoint.connections.len();
+        }
+
+        removed
+    }
+
+    /// Checks whether a connection is a self-loop or has a distance of zero.
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: The index of the waypoint the connection leaves from.
+    /// - `connection`: A reference to the connection.
+    ///
+    /// # Returns
+    ///
+    /// - `bool`: `true` if the connection is degenerate.
+    fn is_degenerate(from: usize, connection: &Connection) -> bool {
+        connection.waypoint_index == from || connection.distance == 0.0
+    }
+
+    /// Assigns connections in the same manner as `assign_all_connections_geohash`, with as
+    /// many neighbors as it takes for the dataset to become connected (see `is_connected`).
+    /// Starting from `start_k`, the number of neighbors is increased by `step` and the
+    /// connections reassigned until the graph is connected. At most 64 rounds are tried,
+    /// and the number of neighbors never exceeds the number of other waypoints, so the
+    /// graph may still be unconnected when this returns on degenerate input.
+    ///
+    /// # Parameters
+    ///
+    /// - `start_k`: The number of nearest neighbors (K) to try first.
+    /// - `step`: The number of neighbors to add after each unconnected round. A `step` of
+    ///   zero is treated as one.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The number of neighbors the final connections were assigned with.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    ///
+    /// // Two clusters of three waypoints, far apart from each other
+    /// for (lat, lon) in [(0.0, 0.0), (0.0, 0.1), (0.0, 0.2), (10.0, 10.0), (10.0, 10.1), (10.0, 10.2)] {
+    ///     dataset.add_new_waypoint(lat, lon).unwrap();
+    /// }
+    ///
+    /// // Each waypoint has to reach past its own cluster for the two to be joined
+    /// let k = dataset.connect_until_connected(1, 1);
+    /// assert_eq!(k, 3);
+    /// assert!(dataset.is_connected());
+    /// ```
+    pub fn connect_until_connected(&mut self, start_k: usize, step: usize) -> usize {
+        const MAX_ROUNDS: usize = 64;
+
+        let max_k = self.waypoints.len().saturating_sub(1);
+        let mut k = start_k.min(max_k);
+
+        for _ in 0..MAX_ROUNDS {
+            self.clear_connections();
+            self.assign_all_connections_geohash(k);
+
+            if k >= max_k || self.is_connected() {
+                break;
+            }
+            k = (k + step.max(1)).min(max_k);
+        }
+
+        k
+    }
+
+    /// Normalizes the connections of every waypoint in the dataset, so that routing is
+    /// bidirectional and output is deterministic regardless of how connections were assigned.
+    /// Each waypoint's connections are sorted by distance (then by index), duplicate
+    /// connections to the same waypoint are removed (keeping the shortest), and every
+    /// connection is given a reverse connection with the same distance and weight if it
+    /// didn't already have one. Calling this again afterwards changes nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    /// dataset.assign_all_connections_geohash(3);
+    /// dataset.assign_all_connections_geohash(3);
+    ///
+    /// dataset.normalize_connections();
+    ///
+    /// let snapshot = |dataset: &zpath::Dataset| -> Vec<Vec<(usize, f32)>> {
+    ///     dataset
+    ///         .waypoints
+    ///         .iter()
+    ///         .map(|w| w.connections.iter().map(|c| (c.waypoint_index, c.distance)).collect())
+    ///         .collect()
+    /// };
+    /// let normalized = snapshot(&dataset);
+    ///
+    /// for (i, connections) in normalized.iter().enumerate() {
+    ///     assert!(connections.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    ///     for &(j, _) in connections {
+    ///         assert!(normalized[j].iter().any(|&(back, _)| back == i));
+    ///     }
+    /// }
+    ///
+    /// // Normalizing is idempotent
+    /// dataset.normalize_connections();
+    /// assert_eq!(snapshot(&dataset), normalized);
+    /// ```
+    pub fn normalize_connections(&mut self) {
+        self.sort_and_dedup_connections();
+
+        let mut missing: Vec<(usize, Connection)> = Vec::new();
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                let reverse_exists = self.waypoints[connection.waypoint_index]
+                    .connections
+                    .iter()
+                    .any(|reverse| reverse.waypoint_index == i);
+
+                if !reverse_exists {
+                    missing.push((
+                        connection.waypoint_index,
+                        Connection {
+                            distance: connection.distance,
+                            weight: connection.weight,
+                            waypoint_index: i,
+                            speed_kph: connection.speed_kph,
+                        },
+                    ));
+                }
+            }
+        }
+
+        for (index, connection) in missing {
+            self.waypoints[index].connections.push(connection);
+        }
+
+        self.sort_and_dedup_connections();
+    }
+
+    /// Matches each of the given connections, as just created by one of the `assign_*`
+    /// methods, with one in the opposite direction with the same distance and weight, unless
+    /// the waypoint it leads to already connects back. Unlike `normalize_connections`, this
+    /// leaves any other connections, and the order of every waypoint's connections, as they
+    /// were.
+    ///
+    /// # Parameters
+    ///
+    /// - `created`: The index of the waypoint each connection leads from, and the connection.
+    fn add_reverse_connections(&mut self, created: &[(usize, Connection)]) {
+        for (from, connection) in created {
+            let to = &mut self.waypoints[connection.waypoint_index];
+            if to
+                .connections
+                .iter()
+                .all(|reverse| reverse.waypoint_index != *from)
+            {
+                to.connections.push(Connection {
+                    waypoint_index: *from,
+                    ..connection.clone()
+                });
+            }
+        }
+    }
+
+    /// Sorts each waypoint's connections by distance, then by index, and removes duplicate
+    /// connections to the same waypoint, keeping the shortest. Used by `normalize_connections`.
+    fn sort_and_dedup_connections(&mut self) {
+        for waypoint in &mut self.waypoints {
+            waypoint.connections.sort_by(|a, b| {
+                a.distance
+                    .total_cmp(&b.distance)
+                    .then(a.waypoint_index.cmp(&b.waypoint_index))
+            });
+
+            let mut seen: HashSet<usize> = HashSet::new();
+            waypoint
+                .connections
+                .retain(|connection| seen.insert(connection.waypoint_index));
+        }
+    }
+
+    /// Lists a waypoint's connections by the labels of the waypoints they lead to, rather
+    /// than by index.
+    ///
+    /// # Parameters
+    ///
+    /// - `index`: The index of the waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(String, f32)>`: The label of each connected waypoint and the distance to it in
+    ///   kilometers, in the same order as the waypoint's connections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// let labels: Vec<String> = dataset.connections_of(0).into_iter().map(|(label, _)| label).collect();
+    /// assert_eq!(labels, vec!["B", "C"]);
+    /// ```
+    pub fn connections_of(&self, index: usize) -> Vec<(String, f32)> {
+        self.waypoints[index]
+            .connections
+            .iter()
+            .map(|connection| {
+                (
+                    self.waypoints[connection.waypoint_index].label.clone(),
+                    connection.distance,
+                )
+            })
+            .collect()
+    }
+
+    /// Aggregates the distances already stored on every waypoint's connections into summary
+    /// statistics. Useful for tuning the number of connections assigned and for spotting
+    /// sparse regions, where connections are unusually long or missing entirely.
+    ///
+    /// # Returns
+    ///
+    /// - `ConnectionStats`: The minimum, maximum, and mean connection distance in kilometers,
+    ///   the total number of connections, and the number of waypoints with fewer connections
+    ///   than the K requested by the most recent `assign_*` call (none if connections were
+    ///   never assigned that way). Distances are all `0.0` if the dataset has no connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    /// dataset.assign_all_connections_geohash(3);
+    ///
+    /// let stats = dataset.connection_stats();
+    /// assert_eq!(stats.connection_count, 300);
+    /// assert_eq!(stats.under_connected, 0);
+    /// assert!(stats.min_distance <= stats.mean_distance && stats.mean_distance <= stats.max_distance);
+    /// ```
+    pub fn connection_stats(&self) -> ConnectionStats {
+        let mut stats = ConnectionStats {
+            min_distance: f32::INFINITY,
+            max_distance: 0.0,
+            mean_distance: 0.0,
+            connection_count: 0,
+            under_connected: 0,
+        };
+        let mut total_distance = 0.0;
+
+        for waypoint in &self.waypoints {
+            if waypoint.connections.len() < self.requested_connections {
+                stats.under_connected += 1;
+            }
+
+            for connection in &waypoint.connections {
+                stats.min_distance = stats.min_distance.min(connection.distance);
+                stats.max_distance = stats.max_distance.max(connection.distance);
+                total_distance += connection.distance;
+                stats.connection_count += 1;
+            }
+        }
+
+        if stats.connection_count > 0 {
+            stats.mean_distance = total_distance / stats.connection_count as f32;
+        } else {
+            stats.min_distance = 0.0;
+        }
+
+        stats
+    }
+
+    /// Summarizes the graph formed by the waypoints' connections, for comparing the
+    /// topologies produced by different ways of assigning connections. Runs in linear time
+    /// in the number of waypoints and connections.
+    ///
+    /// # Returns
+    ///
+    /// - `GraphMetrics`: The number of waypoints and edges, the sum of the edges' distances
+    ///   in kilometers, the average number of connections per waypoint, and whether every
+    ///   connection is matched by one in the opposite direction. If it is, each matched pair
+    ///   counts as a single edge.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// // A and B connect to each other, while C connects to B one way
+    /// dataset.assign_all_connections_naive(1);
+    /// let directed = dataset.graph_metrics();
+    /// assert!(!directed.symmetric);
+    /// assert_eq!(directed.edge_count, 3);
+    ///
+    /// dataset.normalize_connections();
+    /// let undirected = dataset.graph_metrics();
+    /// assert!(undirected.symmetric);
+    /// assert_eq!(undirected.edge_count, 2);
+    /// assert!((undirected.total_edge_length - 333.6).abs() < 1.0);
+    /// assert!((undirected.average_degree - 4.0 / 3.0).abs() < 1e-6);
+    /// ```
+    pub fn graph_metrics(&self) -> GraphMetrics {
+        let mut directed_edges: HashSet<(usize, usize)> = HashSet::new();
+        let mut connection_count = 0;
+        let mut total_distance = 0.0;
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                directed_edges.insert((i, connection.waypoint_index));
+                connection_count += 1;
+                total_distance += connection.distance;
+            }
+        }
+
+        let symmetric = directed_edges
+            .iter()
+            .all(|&(from, to)| directed_edges.contains(&(to, from)));
+
+        let (edge_count, total_edge_length) = if symmetric {
+            (connection_count / 2, total_distance / 2.0)
+        } else {
+            (connection_count, total_distance)
+        };
+
+        let average_degree = if self.waypoints.is_empty() {
+            0.0
+        } else {
+            connection_count as f32 / self.waypoints.len() as f32
+        };
+
+        GraphMetrics {
+            node_count: self.waypoints.len(),
+            edge_count,
+            total_edge_length,
+            average_degree,
+            symmetric,
+        }
+    }
+
+    /// Calculates the shortest route between a starting waypoint and a goal waypoint
+    /// using the A* (A-star) algorithm. The cost of each connection is its `weight`, while
+    /// the remaining cost to the goal is estimated by geographic distance; the route found
+    /// is only guaranteed to be the shortest if no connection's weight is below its distance.
+    ///
+    /// When several routes are equally short, the same one is always returned: the search
+    /// prefers to continue the route furthest along, then the waypoint with the lowest index.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: A reference to the starting waypoint.
+    /// - `goal`: A reference to the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Vec<usize>)`: If a valid route is found, it returns a vector of waypoint indices
+    ///   representing the shortest path from the `start` waypoint to the `goal` waypoint. The
+    ///   vector contains the indices of waypoints in the dataset's 'waypoints' field
+    ///   in the order they should be visited.
+    /// - `None`: If no valid route is found, it returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // Create a dataset with 10,000 waypoints, 5 connections each
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(10000);
+    /// dataset.assign_all_connections_geohash(5);
+    ///
+    /// let start_waypoint = &dataset.waypoints[0];
+    /// let goal_waypoint = &dataset.waypoints[3];
+    ///
+    /// match dataset.get_shortest_route(start_waypoint, goal_waypoint).unwrap() {
+    ///     Some(route) => {
+    ///        for index in route {
+    ///            print!("{}, ", dataset.waypoints[index].label);
+    ///         }
+    ///     }
+    ///     None => {println!("No valid route found.")}
+    /// }
+    ///
+    /// // Two equally short routes around the equator always resolve the same way
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let north = dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// let south = dataset.add_new_waypoint(-1.0, 1.0).unwrap();
+    /// let goal = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    ///
+    /// for (from, to) in [(start, north), (start, south), (north, goal), (south, goal)] {
+    ///     let distance = dataset.waypoints[from].get_distance_to(&dataset.waypoints[to]);
+    ///     dataset.waypoints[from].connections.push(zpath::Connection::new(to, distance));
+    /// }
+    ///
+    /// let route = dataset.get_shortest_route(&dataset.waypoints[start], &dataset.waypoints[goal]);
+    /// assert_eq!(route, Ok(Some(vec![start, north, goal])));
+    ///
+    /// // Waypoints that aren't part of the dataset can't be routed between
+    /// let outsider = zpath::Waypoint::new(0.0, 0.0, String::from("ZZZ"));
+    /// assert_eq!(
+    ///     dataset.get_shortest_route(&outsider, &dataset.waypoints[goal]),
+    ///     Err(zpath::ZpathError::WaypointNotFound(String::from("ZZZ")))
+    /// );
+    /// ```
+    pub fn get_shortest_route(
+        &self,
+        start: &Waypoint,
+        goal: &Waypoint,
+    ) -> Result<Option<Vec<usize>>, ZpathError> {
+        let route = self.get_shortest_route_with_cost(start, goal)?;
+        Ok(route.map(|(route, _)| route))
+    }
+
+    /// Calculates the shortest route between the waypoints at the given indices in the same
+    /// manner as `get_shortest_route`, without having to search the dataset for them.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `goal`: The index of the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Vec<usize>)`: If a valid route is found, it returns the waypoint indices of
+    ///   the route in the order they should be visited.
+    /// - `None`: If no valid route is found, it returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// assert_eq!(dataset.get_shortest_route_by_index(a, b), Some(vec![a, b]));
+    /// ```
+    pub fn get_shortest_route_by_index(&self, start: usize, goal: usize) -> Option<Vec<usize>> {
+        self.get_shortest_route_with_cost_by_index(start, goal)
+            .map(|(route, _)| route)
+    }
+
+    /// Calculates the shortest route between a starting waypoint and a goal waypoint
+    /// using the A* (A-star) algorithm, in the same manner as `get_shortest_route`, and
+    /// also returns the total cost of the route.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: A reference to the starting waypoint.
+    /// - `goal`: A reference to the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: If a valid route is found, it returns the waypoint indices
+    ///   of the route in the order they should be visited, along with the sum of the
+    ///   weights of the connections travelled.
+    /// - `None`: If no valid route is found, it returns `None`.
+    ///
+    /// Returns `ZpathError::WaypointNotFound` if either waypoint isn't part of the dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints(100);
+    /// dataset.assign_all_connections_geohash(5);
+    ///
+    /// if let Ok(Some((route, cost))) =
+    ///     dataset.get_shortest_route_with_cost(&dataset.waypoints[0], &dataset.waypoints[3])
+    /// {
+    ///     println!("Route of {} waypoints covering {:.2}km", route.len(), cost);
+    /// }
+    /// ```
+    pub fn get_shortest_route_with_cost(
+        &self,
+        start: &Waypoint,
+        goal: &Waypoint,
+    ) -> Result<Option<(Vec<usize>, f32)>, ZpathError> {
+        let start_index = self.require_waypoint_index(start)?;
+        let goal_index = self.require_waypoint_index(goal)?;
+
+        Ok(self.get_shortest_route_with_cost_by_index(start_index, goal_index))
+    }
+
+    /// Calculates the shortest route between the waypoints at the given indices in the same
+    /// manner as `get_shortest_route_with_cost`, without having to search the dataset for them.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `goal`: The index of the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: If a valid route is found, it returns the waypoint indices
+    ///   of the route in the order they should be visited, along with the sum of the
+    ///   weights of the connections travelled.
+    /// - `None`: If no valid route is found, it returns `None`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let (route, cost) = dataset.get_shortest_route_with_cost_by_index(b, a).unwrap();
+    /// assert_eq!(route, vec![b, a]);
+    /// assert_eq!(cost, dataset.waypoints[b].get_distance_to(&dataset.waypoints[a]));
+    /// ```
+    pub fn get_shortest_route_with_cost_by_index(
+        &self,
+        start: usize,
+        goal: usize,
+    ) -> Option<(Vec<usize>, f32)> {
+        self.a_star(start, goal, &mut SearchStats::default())
+    }
+
+    /// Calculates the shortest route between a starting waypoint and a goal waypoint in the
+    /// same manner as `get_shortest_route`, while recording statistics about how much work
+    /// the search required. Useful for comparing heuristics and routing strategies.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: A reference to the starting waypoint.
+    /// - `goal`: A reference to the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `(Option<Vec<usize>>, SearchStats)`: The route as returned by `get_shortest_route`,
+    ///   along with the number of waypoints expanded, the peak size of the open set, the
+    ///   number of outdated open set entries skipped, and the cost of the route found (if any).
+    ///
+    /// Returns `ZpathError::WaypointNotFound` if either waypoint isn't part of the dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.generate_waypoints_with(100, zpath::RngKind::XorShift, 42);
+    /// dataset.assign_all_connections_geohash(5);
+    ///
+    /// let (route, stats) = dataset
+    ///     .get_shortest_route_instrumented(&dataset.waypoints[0], &dataset.waypoints[3])
+    ///     .unwrap();
+    /// assert!(stats.nodes_expanded >= 1);
+    /// assert_eq!(route.is_some(), stats.path_cost.is_some());
+    ///
+    /// // Outdated open set entries are skipped, so no waypoint is expanded twice
+    /// assert!(stats.nodes_expanded <= dataset.waypoints.len());
+    /// ```
+    pub fn get_shortest_route_instrumented(
+        &self,
+        start: &Waypoint,
+        goal: &Waypoint,
+    ) -> Result<(Option<Vec<usize>>, SearchStats), ZpathError> {
+        let start_index = self.require_waypoint_index(start)?;
+        let goal_index = self.require_waypoint_index(goal)?;
+
+        let mut stats = SearchStats::default();
+        let route = self.a_star(start_index, goal_index, &mut stats);
+        stats.path_cost = route.as_ref().map(|(_, cost)| *cost);
+
+        Ok((route.map(|(route, _)| route), stats))
+    }
+
+    /// Calculates the shortest route between a starting waypoint and a goal waypoint using
+    /// the A* (A-star) algorithm, in the same manner as `get_shortest_route_with_cost`, but
+    /// with a custom cost for each connection. This allows routing by objectives other than
+    /// distance, such as penalizing certain waypoints, without modifying the connections.
+    ///
+    /// The search estimates the remaining cost to the goal as the geographic distance to it
+    /// multiplied by `heuristic_scale`. The route found is only guaranteed to be the cheapest
+    /// if this never overestimates the true remaining cost; a scale of `0.0` is always safe
+    /// (turning the search into Dijkstra's algorithm), while a scale of `1.0` is safe as long
+    /// as no connection costs less than its distance in kilometers.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: A reference to the starting waypoint.
+    /// - `goal`: A reference to the goal waypoint.
+    /// - `cost_fn`: A closure returning the cost of travelling a connection, given the
+    ///   waypoint it leaves from and the connection itself. Costs should not be negative.
+    /// - `heuristic_scale`: The factor the geographic distance to the goal is multiplied by
+    ///   to estimate the remaining cost.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: If a valid route is found, it returns the waypoint indices
+    ///   of the route in the order they should be visited, along with the sum of the costs
+    ///   of the connections travelled.
+    /// - `None`: If no valid route is found, it returns `None`.
+    ///
+    /// Returns `ZpathError::WaypointNotFound` if either waypoint isn't part of the dataset.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let north = dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// let south = dataset.add_new_waypoint(-1.0, 1.0).unwrap();
+    /// let goal = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    ///
+    /// for (from, to) in [(start, north), (start, south), (north, goal), (south, goal)] {
+    ///     let distance = dataset.waypoints[from].get_distance_to(&dataset.waypoints[to]);
+    ///     dataset.waypoints[from].connections.push(zpath::Connection::new(to, distance));
+    /// }
+    ///
+    /// // Avoid travelling through waypoint B by making every connection into it very costly
+    /// let avoid_north = |_: &zpath::Waypoint, connection: &zpath::Connection| {
+    ///     if connection.waypoint_index == north {
+    ///         connection.distance * 10.0
+    ///     } else {
+    ///         connection.distance
+    ///     }
+    /// };
+    ///
+    /// let (route, _cost) = dataset
+    ///     .get_shortest_route_with(&dataset.waypoints[start], &dataset.waypoints[goal], avoid_north, 1.0)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(route, vec![start, south, goal]);
+    ///
+    /// // Costs cheaper than the distance are allowed, though the route may not be the cheapest
+    /// let half = |_: &zpath::Waypoint, connection: &zpath::Connection| connection.distance * 0.5;
+    /// let cheap = dataset
+    ///     .get_shortest_route_with(&dataset.waypoints[start], &dataset.waypoints[goal], half, 1.0)
+    ///     .unwrap();
+    /// assert!(cheap.is_some());
+    /// ```
+    pub fn get_shortest_route_with<F>(
+        &self,
+        start: &Waypoint,
+        goal: &Waypoint,
+        cost_fn: F,
+        heuristic_scale: f32,
+    ) -> Result<Option<(Vec<usize>, f32)>, ZpathError>
+    where
+        F: Fn(&Waypoint, &Connection) -> f32,
+    {
+        let start_index = self.require_waypoint_index(start)?;
+        let goal_index = self.require_waypoint_index(goal)?;
+
+        Ok(self.a_star_with(
+            start_index,
+            goal_index,
+            &mut SearchStats::default(),
+            cost_fn,
+            heuristic_scale,
+        ))
+    }
+
+    /// Finds the shortest round trip that starts and ends at a waypoint and visits at least
+    /// one other waypoint, e.g. for patrol or loop routes. Each of the waypoint's neighbors
+    /// is tried in turn: the route leaves along the connection to that neighbor and returns
+    /// by the shortest route that doesn't travel straight back along the neighbor's own
+    /// connection to the waypoint.
+    ///
+    /// # Arguments
+    ///
+    /// - `node`: The index of the waypoint the cycle starts and ends at.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: The waypoint indices of the cycle, beginning and ending
+    ///   with `node`, along with the sum of the weights of the connections travelled.
+    /// - `None`: If the waypoint has fewer than two connections or no cycle exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// // The four corners of the square connect to their two adjacent corners
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// let (cycle, cost) = dataset.shortest_cycle_through(0).unwrap();
+    /// assert_eq!(cycle.len(), 5);
+    /// assert_eq!((cycle[0], cycle[4]), (0, 0));
+    /// assert!(cost > 440.0 && cost < 450.0);
+    ///
+    /// // A waypoint with a single connection has no way around
+    /// dataset.waypoints[4].connections.truncate(1);
+    /// assert!(dataset.shortest_cycle_through(4).is_none());
+    /// ```
+    pub fn shortest_cycle_through(&self, node: usize) -> Option<(Vec<usize>, f32)> {
+        let connections = &self.waypoints[node].connections;
+        if connections.len() < 2 {
+            return None;
+        }
+
+        let mut shortest: Option<(Vec<usize>, f32)> = None;
+        for outbound in connections {
+            let neighbor = outbound.waypoint_index;
+            if neighbor == node {
+                continue;
+            }
+
+            // Rule out the neighbor's direct connection back to the waypoint
+            let neighbor_waypoint = &self.waypoints[neighbor];
+            let cost_fn = |from: &Waypoint, connection: &Connection| {
+                if std::ptr::eq(from, neighbor_waypoint) && connection.waypoint_index == node {
+                    f32::INFINITY
+                } else {
+                    connection.weight
+                }
+            };
+
+            let Some((route, cost)) =
+                self.a_star_with(neighbor, node, &mut SearchStats::default(), cost_fn, 1.0)
+            else {
+                continue;
+            };
+
+            let total = outbound.weight + cost;
+            if total.is_finite() && shortest.as_ref().is_none_or(|(_, best)| total < *best) {
+                let mut cycle = Vec::with_capacity(route.len() + 1);
+                cycle.push(node);
+                cycle.extend(route);
+                shortest = Some((cycle, total));
+            }
+        }
+
+        shortest
+    }
+
+    /// Routes from a starting waypoint as near as possible to a goal, for routing on graphs
+    /// which may be split into disconnected parts. An A* (A-star) search is run towards the
+    /// goal; if a waypoint at the goal's position can be reached, such as the goal itself or a
+    /// copy of it, the shortest route to it is returned as by `get_shortest_route`. Otherwise,
+    /// every waypoint reachable from the start ends up being settled, and the route to
+    /// whichever of those is nearest to the goal (measured with the dataset's `Metric`) is
+    /// returned instead.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `goal`: A reference to the goal, which needn't be one of the dataset's waypoints.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: The waypoint indices of the route in the order they
+    ///   should be visited, along with the sum of the weights of the connections travelled.
+    ///   The route ends at the goal if it was reached, and consists of just `start` if no
+    ///   reachable waypoint is nearer to the goal than `start` itself.
+    /// - `None`: If `start` is out of range or has been deactivated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let shore = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let island = dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// let distance = dataset.waypoints[start].get_distance_to(&dataset.waypoints[shore]);
+    /// dataset.waypoints[start].connections.push(zpath::Connection::new(shore, distance));
+    ///
+    /// // The island can't be reached, so the route gets as near to it as it can
+    /// let goal = dataset.waypoints[island].clone();
+    /// let (route, cost) = dataset.get_closest_reachable(start, &goal).unwrap();
+    /// assert_eq!(route, vec![start, shore]);
+    /// assert_eq!(cost, distance);
+    /// ```
+    pub fn get_closest_reachable(
+        &self,
+        start: usize,
+        goal: &Waypoint,
+    ) -> Option<(Vec<usize>, f32)> {
+        if self
+            .waypoints
+            .get(start)
+            .is_none_or(|waypoint| !waypoint.active)
+        {
+            return None;
+        }
+
+        // The nearest expanded waypoint to the goal so far, and its distance from the goal
+        let mut closest = (start, self.metric_distance(&self.waypoints[start], goal));
+
+        let search = self.a_star_search(
+            start,
+            goal,
+            &mut SearchStats::default(),
+            |_, connection| connection.weight,
+            1.0,
+            |index| {
+                let remaining = self.metric_distance(&self.waypoints[index], goal);
+                if remaining < closest.1 {
+                    closest = (index, remaining);
+                }
+
+                // A waypoint at the goal itself can't be bettered
+                remaining == 0.0
+            },
+        );
+
+        Some(search.route_to(closest.0))
+    }
+
+    /// Runs the A* (A-star) search underlying the public routing methods, recording the
+    /// work done into the given statistics.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_index`: The index of the starting waypoint.
+    /// - `goal_index`: The index of the goal waypoint.
+    /// - `stats`: A mutable reference to the statistics to record into.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(Vec<usize>, f32)>`: The route and its cost, or `None` if no route is found.
+    fn a_star(
+        &self,
+        start_index: usize,
+        goal_index: usize,
+        stats: &mut SearchStats,
+    ) -> Option<(Vec<usize>, f32)> {
+        self.a_star_with(
+            start_index,
+            goal_index,
+            stats,
+            |_, connection| connection.weight,
+            1.0,
+        )
+    }
+
+    /// Runs the A* (A-star) search in the same manner as `a_star`, but with the cost of each
+    /// connection given by `cost_fn` and the heuristic scaled by `heuristic_scale`.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_index`: The index of the starting waypoint.
+    /// - `goal_index`: The index of the goal waypoint.
+    /// - `stats`: A mutable reference to the statistics to record into.
+    /// - `cost_fn`: Returns the cost of travelling a connection from a waypoint.
+    /// - `heuristic_scale`: The factor the geographic distance to the goal is multiplied by
+    ///   to estimate the remaining cost.
+    ///
+    /// # Returns
+    ///
+    /// - `Option<(Vec<usize>, f32)>`: The route and its cost, or `None` if no route is found.
+    fn a_star_with<F>(
+        &self,
+        start_index: usize,
+        goal_index: usize,
+        stats: &mut SearchStats,
+        cost_fn: F,
+        heuristic_scale: f32,
+    ) -> Option<(Vec<usize>, f32)>
+    where
+        F: Fn(&Waypoint, &Connection) -> f32,
+    {
+        if !self.waypoints[start_index].active || !self.waypoints[goal_index].active {
+            return None;
+        }
+
+        let search = self.a_star_search(
+            start_index,
+            &self.waypoints[goal_index],
+            stats,
+            cost_fn,
+            heuristic_scale,
+            |index| index == goal_index,
+        );
+
+        search
+            .stopped_at
+            .map(|goal_index| search.route_to(goal_index))
+    }
+
+    /// Runs the A* (A-star) search loop shared by `a_star_with` and `get_closest_reachable`.
+    /// Waypoints are expanded in order of their cost from the start plus the estimated cost
+    /// to the goal, until `stop_at` returns `true` for one or every reachable waypoint has
+    /// been expanded. Routes never pass through deactivated waypoints.
+    ///
+    /// # Arguments
+    ///
+    /// - `start_index`: The index of the starting waypoint, which should be active.
+    /// - `goal`: A reference to the goal the heuristic estimates the remaining cost to,
+    ///   which needn't be one of the dataset's waypoints.
+    /// - `stats`: A mutable reference to the statistics to record into.
+    /// - `cost_fn`: Returns the cost of travelling a connection from a waypoint.
+    /// - `heuristic_scale`: The factor the geographic distance to the goal is multiplied by
+    ///   to estimate the remaining cost.
+    /// - `stop_at`: Called with each waypoint as it's expanded, returning whether to stop the
+    ///   search there.
+    ///
+    /// # Returns
+    ///
+    /// - `AStarSearch`: The costs and predecessors of the waypoints visited, and the waypoint
+    ///   the search stopped at, if any.
+    fn a_star_search<F>(
+        &self,
+        start_index: usize,
+        goal: &Waypoint,
+        stats: &mut SearchStats,
+        cost_fn: F,
+        heuristic_scale: f32,
+        mut stop_at: impl FnMut(usize) -> bool,
+    ) -> AStarSearch
+    where
+        F: Fn(&Waypoint, &Connection) -> f32,
+    {
+        let capacity = self.waypoints.len().min(SEARCH_CAPACITY_HINT);
+        let mut open_set: BinaryHeap<AStarNode> = BinaryHeap::with_capacity(capacity);
+        let mut came_from: HashMap<usize, usize> = HashMap::with_capacity(capacity);
+        let mut g_scores: HashMap<usize, f32> = HashMap::with_capacity(capacity);
+
+        // Initialize the open set and g_scores map with the starting point
+        g_scores.insert(start_index, 0.0);
+        open_set.push(AStarNode {
+            f_score: 0.0,
+            g_score: 0.0,
+            waypoint_index: start_index,
+        });
+        stats.max_open_set_size = 1;
+
+        // While there are still routes to explore in the open set...
+        while let Some(node) = open_set.pop() {
+            let current_index = node.waypoint_index;
+            let current_waypoint = &self.waypoints[current_index];
+
+            // A cheaper route to this waypoint was found after this entry was pushed, and
+            // the entry for that route has already been expanded, so skip this one
+            if node.g_score > g_scores[&current_index] {
+                stats.stale_entries_skipped += 1;
+                continue;
+            }
+            stats.nodes_expanded += 1;
+
+            if stop_at(current_index) {
+                return AStarSearch {
+                    came_from,
+                    g_scores,
+                    stopped_at: Some(current_index),
+                };
+            }
+
+            // Explore neighbors of the current waypoint
+            for neighbor in &current_waypoint.connections {
+                let neighbor_index = neighbor.waypoint_index;
+                // Routes never pass through deactivated waypoints
+                if !self.waypoints[neighbor_index].active {
+                    continue;
+                }
+                let g_score = g_scores[&current_index] + cost_fn(current_waypoint, neighbor);
+                debug_assert!(
+                    self.connection_agrees_with_metric(current_waypoint, neighbor),
+                    "the connection from waypoint {} to waypoint {} is shorter than the \
+                     metric's distance between them; were the connections assigned with a \
+                     different metric?",
+                    current_index,
+                    neighbor_index
+                );
+
+                // If the neighbor has not been visited or a shorter path is found...
+                if !g_scores.contains_key(&neighbor_index) || g_score < g_scores[&neighbor_index] {
+                    // This is a better path to the neighbor
+                    came_from.insert(neighbor_index, current_index);
+                    g_scores.insert(neighbor_index, g_score);
+
+                    // Add the neighbor to the open set for further exploration
+                    let h_score = heuristic_scale
+                        * self.metric_distance(&self.waypoints[neighbor_index], goal);
+                    open_set.push(AStarNode {
+                        f_score: g_score + h_score,
+                        g_score,
+                        waypoint_index: neighbor_index,
+                    });
+                    stats.max_open_set_size = stats.max_open_set_size.max(open_set.len());
+                }
+            }
+        }
+
+        AStarSearch {
+            came_from,
+            g_scores,
+            stopped_at: None,
+        }
+    }
+
+    /// Checks that a connection's distance is no shorter than the metric's distance between
+    /// its waypoints. The routing heuristic only ever underestimates the remaining distance
+    /// when this holds for every connection, which it won't if, for example, the connections
+    /// were assigned using a different metric than the dataset's current one. The cost of
+    /// travelling a connection may still be cheaper than its distance, e.g. through its
+    /// `weight` or a custom cost function, as the caller chose that cost.
+    ///
+    /// # Arguments
+    ///
+    /// - `from`: A reference to the waypoint the connection leaves from.
+    /// - `connection`: A reference to the connection.
+    ///
+    /// # Returns
+    ///
+    /// - `bool`: `true` if the connection is at least as long as the metric's distance,
+    ///   allowing for rounding error.
+    fn connection_agrees_with_metric(&self, from: &Waypoint, connection: &Connection) -> bool {
+        let metric_distance =
+            self.metric_distance(from, &self.waypoints[connection.waypoint_index]);
+        let tolerance = 1e-3 * metric_distance.max(1.0);
+
+        metric_distance <= connection.distance + tolerance
+    }
+
+    /// Checks whether a route exists from one waypoint to another by following
+    /// connections. This is a breadth-first search which stops as soon as the goal is
+    /// reached, and is much cheaper than `get_shortest_route` when only connectivity
+    /// matters, e.g. when auditing many pairs of waypoints.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `goal`: The index of the goal waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `bool`: `true` if `goal` can be reached from `start` (including when they're the
+    ///   same waypoint) without passing through a deactivated waypoint, `false` otherwise or
+    ///   if either index is out of range or either waypoint is deactivated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// // Each waypoint connects only to its nearest neighbor, which always lies to the West
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// assert!(dataset.is_reachable(2, 0));
+    /// assert!(!dataset.is_reachable(0, 2));
+    ///
+    /// // The only route from C to A passes through B
+    /// dataset.deactivate(1);
+    /// assert!(!dataset.is_reachable(2, 0));
+    /// ```
+    pub fn is_reachable(&self, start: usize, goal: usize) -> bool {
+        if start >= self.waypoints.len() || goal >= self.waypoints.len() {
+            return false;
+        }
+        if !self.waypoints[start].active || !self.waypoints[goal].active {
+            return false;
+        }
+
+        let mut visited: HashSet<usize> = HashSet::from([start]);
+        let mut queue: VecDeque<usize> = VecDeque::from([start]);
+
+        while let Some(current) = queue.pop_front() {
+            if current == goal {
+                return true;
+            }
+
+            for connection in &self.waypoints[current].connections {
+                if self.waypoints[connection.waypoint_index].active
+                    && visited.insert(connection.waypoint_index)
+                {
+                    queue.push_back(connection.waypoint_index);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Checks whether every active waypoint in the dataset can reach every other by following
+    /// connections, so that a route exists between any pair of them. Deactivated waypoints are
+    /// ignored, and routes can't pass through them. Runs a breadth-first search from the first
+    /// active waypoint along the connections and another against them; the graph is connected
+    /// if both searches reach every active waypoint. When the dataset isn't `directed`, the
+    /// connections are assumed to be symmetric and only the first search is needed.
+    ///
+    /// # Returns
+    ///
+    /// - `bool`: `true` if every active waypoint can reach every other, including when the
+    ///   dataset has fewer than two active waypoints.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// // C can reach A and B, but nothing connects back to C
+    /// dataset.assign_all_connections_naive(1);
+    /// assert!(!dataset.is_connected());
+    ///
+    /// dataset.normalize_connections();
+    /// assert!(dataset.is_connected());
+    ///
+    /// // Without B in the middle, A and C can no longer reach each other
+    /// dataset.deactivate(1);
+    /// assert!(!dataset.is_connected());
+    /// ```
+    pub fn is_connected(&self) -> bool {
+        let active_count = self.waypoints.iter().filter(|w| w.active).count();
+        let Some(first) = self.waypoints.iter().position(|w| w.active) else {
+            return true;
+        };
+        if active_count < 2 {
+            return true;
+        }
+
+        let reverse_index = if self.directed {
+            self.build_reverse_index()
+        } else {
+            Vec::new()
+        };
+        let forward: Vec<&[Connection]> = self
+            .waypoints
+            .iter()
+            .map(|waypoint| waypoint.connections.as_slice())
+            .collect();
+        let reverse: Vec<&[Connection]> = reverse_index.iter().map(Vec::as_slice).collect();
+
+        let reaches_all = |adjacency: &[&[Connection]]| {
+            let mut visited = vec![false; self.waypoints.len()];
+            let mut queue: VecDeque<usize> = VecDeque::from([first]);
+            visited[first] = true;
+            let mut visited_count = 1;
+
+            while let Some(current) = queue.pop_front() {
+                for connection in adjacency[current] {
+                    let neighbor = connection.waypoint_index;
+                    if !visited[neighbor] && self.waypoints[neighbor].active {
+                        visited[neighbor] = true;
+                        visited_count += 1;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+
+            visited_count == active_count
+        };
+
+        reaches_all(&forward) && (!self.directed || reaches_all(&reverse))
+    }
+
+    /// Builds an index of the connections leading into each waypoint, as the reverse of
+    /// each waypoint's `connections`, which only lead out of it. Useful for searching
+    /// backwards from a goal, such as to find every waypoint that can reach it.
+    ///
+    /// The index is a snapshot: it isn't updated as connections change, and must be rebuilt
+    /// after connections are assigned, cleared, or modified.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Vec<Connection>>`: For each waypoint, a connection to every waypoint that
+    ///   connects to it, carrying the distance, weight, and speed of the original connection.
+    ///   Indexed in the same way as `waypoints`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    ///
+    /// // A and C both connect to B, while only B connects to A
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let reverse = dataset.build_reverse_index();
+    /// let into_b: Vec<usize> = reverse[1].iter().map(|c| c.waypoint_index).collect();
+    /// assert_eq!(into_b, vec![0, 2]);
+    /// assert!(reverse[2].is_empty());
+    /// ```
+    pub fn build_reverse_index(&self) -> Vec<Vec<Connection>> {
+        let mut reverse_index: Vec<Vec<Connection>> = vec![Vec::new(); self.waypoints.len()];
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                reverse_index[connection.waypoint_index].push(Connection {
+                    waypoint_index: i,
+                    ..connection.clone()
+                });
+            }
+        }
+
+        reverse_index
+    }
+
+    /// Calculates a route that starts at the first of the given stops and visits each of
+    /// the following stops in order, by finding the shortest route between each pair of
+    /// consecutive stops with `get_shortest_route_with_cost_by_index` and joining the results.
+    ///
+    /// # Arguments
+    ///
+    /// - `stops`: The indices of the waypoints to visit, in the order they should be visited.
+    ///
+    /// # Returns
+    ///
+    /// - `Some((Vec<usize>, f32))`: If every leg of the trip has a valid route, it returns the
+    ///   waypoint indices of the full route in the order they should be visited, along with
+    ///   its total cost. Each stop between the first and last appears once where legs join.
+    /// - `None`: If any leg of the trip has no valid route, if any stop is out of range, or
+    ///   if no stops are given.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 6.0).unwrap();
+    ///
+    /// // Each waypoint connects only to its nearest neighbor, which always lies to the West
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let (route, _cost) = dataset.get_route_through(&[3, 1, 0]).unwrap();
+    /// assert_eq!(route, vec![3, 2, 1, 0]);
+    ///
+    /// // There is no way to travel East from waypoint A
+    /// assert!(dataset.get_route_through(&[0, 3]).is_none());
+    /// assert!(dataset.get_route_through(&[3, 7]).is_none());
+    /// ```
+    pub fn get_route_through(&self, stops: &[usize]) -> Option<(Vec<usize>, f32)> {
+        if stops.iter().any(|&stop| stop >= self.waypoints.len()) {
+            return None;
+        }
+
+        let mut route = vec![*stops.first()?];
+        let mut total_cost = 0.0;
+
+        for leg in stops.windows(2) {
+            let (leg_route, leg_cost) =
+                self.get_shortest_route_with_cost_by_index(leg[0], leg[1])?;
+
+            // The first waypoint of each leg is the last waypoint of the previous leg
+            route.extend_from_slice(&leg_route[1..]);
+            total_cost += leg_cost;
+        }
+
+        Some((route, total_cost))
+    }
+
+    /// Orders a set of stops into a tour using the nearest neighbor heuristic: starting
+    /// from `start`, the nearest unvisited stop by great-circle distance (not by route) is
+    /// repeatedly visited next until none remain. This runs in O(n²) time for n stops and
+    /// usually produces a reasonably short tour, but it is a heuristic and is not
+    /// guaranteed to find the optimal order.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the waypoint to start the tour from.
+    /// - `stops`: The indices of the waypoints to visit, in any order.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints in the order they should be visited,
+    ///   beginning with `start`. This can be passed directly to `get_route_through`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    ///
+    /// assert_eq!(dataset.greedy_tour(0, &[1, 2, 3]), vec![0, 2, 3, 1]);
+    /// ```
+    pub fn greedy_tour(&self, start: usize, stops: &[usize]) -> Vec<usize> {
+        let mut unvisited: Vec<usize> = Vec::with_capacity(stops.len());
+        for &stop in stops {
+            if stop != start && !unvisited.contains(&stop) {
+                unvisited.push(stop);
+            }
+        }
+
+        let mut tour = Vec::with_capacity(unvisited.len() + 1);
+        tour.push(start);
+        let mut current = start;
+
+        while !unvisited.is_empty() {
+            let current_waypoint = &self.waypoints[current];
+            let (nearest_position, _) = unvisited
+                .iter()
+                .enumerate()
+                .map(|(i, &stop)| (i, current_waypoint.get_distance_to(&self.waypoints[stop])))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+                .unwrap();
+
+            current = unvisited.swap_remove(nearest_position);
+            tour.push(current);
+        }
+
+        tour
+    }
+
+    /// Calculates the cost of the shortest route from a starting waypoint to every waypoint
+    /// reachable from it using Dijkstra's algorithm, stopping once the remaining routes would
+    /// cost more than the given maximum.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `max_cost`: The maximum route cost to explore; `f32::INFINITY` to explore everything.
+    ///
+    /// # Returns
+    ///
+    /// - `HashMap<usize, f32>`: The route cost to each reached waypoint, keyed by index,
+    ///   including the starting waypoint at a cost of `0.0`. Empty if the starting waypoint
+    ///   is deactivated.
+    fn get_route_costs_from(&self, start: usize, max_cost: f32) -> HashMap<usize, f32> {
+        if !self.waypoints[start].active {
+            return HashMap::new();
+        }
+
+        let mut open_set: BinaryHeap<AStarNode> = BinaryHeap::new();
+        let mut g_scores: HashMap<usize, f32> = HashMap::new();
+        let mut settled: HashMap<usize, f32> = HashMap::new();
+
+        g_scores.insert(start, 0.0);
+        open_set.push(AStarNode {
+            f_score: 0.0,
+            g_score: 0.0,
+            waypoint_index: start,
+        });
+
+        while let Some(node) = open_set.pop() {
+            let current_index = node.waypoint_index;
+
+            // Skip entries for waypoints already reached more cheaply
+            if settled.contains_key(&current_index) {
+                continue;
+            }
+            settled.insert(current_index, node.f_score);
+
+            for neighbor in &self.waypoints[current_index].connections {
+                let neighbor_index = neighbor.waypoint_index;
+                // Routes never pass through deactivated waypoints
+                if !self.waypoints[neighbor_index].active {
+                    continue;
+                }
+                let g_score = node.f_score + neighbor.weight;
+
+                if g_score <= max_cost
+                    && (!g_scores.contains_key(&neighbor_index)
+                        || g_score < g_scores[&neighbor_index])
+                {
+                    g_scores.insert(neighbor_index, g_score);
+                    open_set.push(AStarNode {
+                        f_score: g_score,
+                        g_score,
+                        waypoint_index: neighbor_index,
+                    });
+                }
+            }
+        }
+
+        settled
+    }
+
+    /// Finds every waypoint that can be reached from a starting waypoint within a cost
+    /// budget, along with the cost of the cheapest route to each, e.g. to draw an isochrone
+    /// of everywhere reachable within 30 minutes. The search expands outwards from the start
+    /// in order of route cost and stops once every remaining route exceeds the budget.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `budget`: The maximum route cost (inclusive), as the sum of connection weights.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(usize, f32)>`: The index of each reachable waypoint and its route cost,
+    ///   sorted by cost and then by index. Includes the starting waypoint at a cost of `0.0`,
+    ///   unless it's deactivated, in which case nothing is reachable. Routes never pass
+    ///   through deactivated waypoints.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let near = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// // One degree of longitude at the equator is about 111km
+    /// let reachable = dataset.reachable_within(start, 150.0);
+    /// let indices: Vec<usize> = reachable.iter().map(|&(index, _)| index).collect();
+    /// assert_eq!(indices, vec![start, near]);
+    /// assert_eq!(reachable[0].1, 0.0);
+    /// ```
+    pub fn reachable_within(&self, start: usize, budget: f32) -> Vec<(usize, f32)> {
+        let mut reachable: Vec<(usize, f32)> = self
+            .get_route_costs_from(start, budget)
+            .into_iter()
+            .collect();
+
+        reachable.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        reachable
+    }
+
+    /// Finds the waypoints whose shortest route from a starting waypoint takes exactly a
+    /// given number of connections, regardless of their distances, e.g. to measure how
+    /// quickly the graph fans out or to sample waypoints in rings around a start. Unlike
+    /// `reachable_within`, which limits the cost of routes, this counts connections only.
+    ///
+    /// # Arguments
+    ///
+    /// - `start`: The index of the starting waypoint.
+    /// - `hops`: The number of connections followed to reach each waypoint.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints at exactly `hops` connections from
+    ///   `start`, in ascending order. A `hops` of `0` gives just `start`. Routes never pass
+    ///   through deactivated waypoints, and nothing is reached from a deactivated `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// for lon in [0.0, 1.0, 3.0, 6.0] {
+    ///     dataset.add_new_waypoint(0.0, lon).unwrap();
+    /// }
+    /// dataset.assign_all_connections_naive(1);
+    /// dataset.normalize_connections();
+    ///
+    /// // The waypoints form a path 0 - 1 - 2 - 3
+    /// assert_eq!(dataset.nodes_at_hop_distance(1, 1), vec![0, 2]);
+    /// assert_eq!(dataset.nodes_at_hop_distance(1, 2), vec![3]);
+    /// assert!(dataset.nodes_at_hop_distance(1, 3).is_empty());
+    ///
+    /// dataset.deactivate(2);
+    /// assert!(dataset.nodes_at_hop_distance(1, 2).is_empty());
+    /// ```
+    pub fn nodes_at_hop_distance(&self, start: usize, hops: usize) -> Vec<usize> {
+        if !self.waypoints[start].active {
+            return Vec::new();
+        }
+
+        let mut visited = vec![false; self.waypoints.len()];
+        visited[start] = true;
+        let mut frontier = vec![start];
+
+        // Expand one hop at a time, keeping only waypoints not reached in fewer hops
+        for _ in 0..hops {
+            let mut next_frontier = Vec::new();
+            for &current in &frontier {
+                for connection in &self.waypoints[current].connections {
+                    let neighbor = connection.waypoint_index;
+                    if !visited[neighbor] && self.waypoints[neighbor].active {
+                        visited[neighbor] = true;
+                        next_frontier.push(neighbor);
+                    }
+                }
+            }
+
+            if next_frontier.is_empty() {
+                return next_frontier;
+            }
+            frontier = next_frontier;
+        }
+
+        frontier.sort_unstable();
+        frontier
+    }
+
+    /// Builds a matrix of the shortest route costs between every pair of waypoints in the
+    /// dataset, by running Dijkstra's algorithm from every waypoint.
+    ///
+    /// This runs a full search from each of the N waypoints, costing roughly
+    /// O(N · (N + E) · log N) time for E connections and O(N²) memory; it is intended for
+    /// small, curated datasets of perhaps a few thousand waypoints, not large generated ones.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<Vec<Option<f32>>>`: An N×N matrix where `matrix[i][j]` is the cost of the shortest
+    ///   route from waypoint `i` to waypoint `j`, or `None` if `j` is unreachable from `i`.
+    ///   Routes never pass through deactivated waypoints, so their rows and columns are `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 3.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let matrix = dataset.distance_matrix();
+    /// assert_eq!(matrix[0][0], Some(0.0));
+    /// assert!(matrix[2][0].is_some());
+    /// assert!(matrix[0][2].is_none());
+    /// ```
+    pub fn distance_matrix(&self) -> Vec<Vec<Option<f32>>> {
+        (0..self.waypoints.len())
+            .map(|start| {
+                let costs = self.get_route_costs_from(start, f32::INFINITY);
+                (0..self.waypoints.len())
+                    .map(|goal| costs.get(&goal).copied())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Builds a minimum spanning tree over the dataset's connections using Kruskal's
+    /// algorithm: the set of connections that joins every waypoint together with the
+    /// smallest total distance. Connections are treated as undirected, so a connection
+    /// in either direction joins its two waypoints.
+    ///
+    /// If the connections don't join every waypoint together, a tree can only be built for
+    /// each connected group of waypoints; in that case the tree of the largest group is
+    /// returned (the group containing the lowest index, if several are equally large).
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(usize, usize, f32)>`: The edges of the tree as `(from, to, distance)`, with
+    ///   `from < to`, sorted by distance. Empty if the dataset has fewer than two waypoints
+    ///   or no connections.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    /// dataset.add_new_waypoint(50.0, 50.0).unwrap();
+    /// dataset.add_new_waypoint(50.0, 51.0).unwrap();
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// // The remote pair connect to each other and to the square, so every waypoint is joined
+    /// let tree = dataset.minimum_spanning_tree();
+    /// assert_eq!(tree.len(), 5);
+    /// assert!(tree.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+    ///
+    /// // Of two equally large groups, the one containing waypoint 0 is kept
+    /// let mut pairs = zpath::Dataset::new();
+    /// pairs.add_new_waypoint(0.0, 0.0).unwrap();
+    /// pairs.add_new_waypoint(50.0, 50.0).unwrap();
+    /// pairs.add_new_waypoint(50.0, 51.0).unwrap();
+    /// pairs.add_new_waypoint(0.0, 1.0).unwrap();
+    /// pairs.assign_all_connections_naive(1);
+    ///
+    /// let tree = pairs.minimum_spanning_tree();
+    /// assert_eq!(tree.len(), 1);
+    /// assert_eq!((tree[0].0, tree[0].1), (0, 3));
+    /// ```
+    pub fn minimum_spanning_tree(&self) -> Vec<(usize, usize, f32)> {
+        let edges = self.undirected_edges();
+
+        let mut parents: Vec<usize> = (0..self.waypoints.len()).collect();
+        let mut forest: Vec<(usize, usize, f32)> = Vec::new();
+        for (from, to, distance) in edges {
+            let (from_root, to_root) = (find_root(&mut parents, from), find_root(&mut parents, to));
+            if from_root != to_root {
+                parents[from_root] = to_root;
+                forest.push((from, to, distance));
+            }
+        }
+
+        // Keep only the tree spanning the largest group of connected waypoints
+        let mut group_sizes: HashMap<usize, usize> = HashMap::new();
+        for index in 0..self.waypoints.len() {
+            *group_sizes
+                .entry(find_root(&mut parents, index))
+                .or_insert(0) += 1;
+        }
+
+        // Groups are visited in order of their lowest index, so ties go to the earliest
+        let mut largest: Option<(usize, usize)> = None;
+        for index in 0..self.waypoints.len() {
+            let root = find_root(&mut parents, index);
+            let size = group_sizes[&root];
+
+            if largest.is_none_or(|(_, largest_size)| size > largest_size) {
+                largest = Some((root, size));
+            }
+        }
+
+        match largest {
+            Some((largest_root, _)) => forest
+                .into_iter()
+                .filter(|&(from, _, _)| find_root(&mut parents, from) == largest_root)
+                .collect(),
+            None => forest,
+        }
+    }
+
+    /// Removes the longest connections from the graph without splitting it apart, e.g. to
+    /// tidy up a generated graph into a more local topology. Connections are treated as
+    /// undirected edges, so removing an edge removes the connections in both directions.
+    ///
+    /// The longest `fraction` of the edges are considered for removal, longest first, and an
+    /// edge is kept if removing it would leave its two waypoints with no other path between
+    /// them. This keeps exactly the candidates that are part of the minimum spanning forest,
+    /// which is found incrementally with union-find. Every route that existed
+    /// through undirected edges still exists afterwards, so reachability for
+    /// `get_shortest_route` is preserved as long as the connections are symmetric (see
+    /// `normalize_connections`).
+    ///
+    /// # Parameters
+    ///
+    /// - `fraction`: The fraction of the edges to consider for removal, clamped to between
+    ///   `0.0` and `1.0`.
+    ///
+    /// # Returns
+    ///
+    /// - `usize`: The number of edges actually removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let c = dataset.add_new_waypoint(1.0, 0.0).unwrap();
+    /// let remote = dataset.add_new_waypoint(0.0, 10.0).unwrap();
+    /// dataset.assign_all_connections_naive(3);
+    ///
+    /// // Of the six edges, the three longest all lead to the remote waypoint, and two of them
+    /// // can be removed while keeping it connected
+    /// assert_eq!(dataset.prune_longest_edges(0.5), 2);
+    /// assert!(dataset.is_connected());
+    /// assert_eq!(dataset.connections_of(remote).len(), 1);
+    /// assert_eq!(dataset.connections_of(a).len(), 2);
+    /// assert!(dataset.is_reachable(c, b));
+    ///
+    /// // Connections in opposite directions may differ in distance, but still form one edge
+    /// use zpath::Connection;
+    ///
+    /// let mut path = zpath::Dataset::new();
+    /// let a = path.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = path.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let c = path.add_new_waypoint(0.0, 2.0).unwrap();
+    /// path.waypoints[a].connections.push(Connection::new(b, 100.0));
+    /// path.waypoints[b].connections.push(Connection::new(a, 500.0));
+    /// path.waypoints[b].connections.push(Connection::new(c, 200.0));
+    /// path.waypoints[c].connections.push(Connection::new(b, 300.0));
+    ///
+    /// assert_eq!(path.prune_longest_edges(1.0), 0);
+    /// assert!(path.is_connected());
+    /// ```
+    pub fn prune_longest_edges(&mut self, fraction: f32) -> usize {
+        let edges = self.undirected_edges();
+        let candidates = (fraction.clamp(0.0, 1.0) * edges.len() as f32) as usize;
+        let first_candidate = edges.len() - candidates;
+
+        // The shorter edges are never removed, so join their waypoints up front
+        let mut parents: Vec<usize> = (0..self.waypoints.len()).collect();
+        for &(from, to, _) in &edges[..first_candidate] {
+            let (from_root, to_root) = (find_root(&mut parents, from), find_root(&mut parents, to));
+            parents[from_root] = to_root;
+        }
+
+        // Going from the shortest candidate to the longest, an edge is needed only if its
+        // waypoints aren't already joined by the edges before it
+        let mut removed: HashSet<(usize, usize)> = HashSet::new();
+        for &(from, to, _) in &edges[first_candidate..] {
+            let (from_root, to_root) = (find_root(&mut parents, from), find_root(&mut parents, to));
+            if from_root == to_root {
+                removed.insert((from, to));
+            } else {
+                parents[from_root] = to_root;
+            }
+        }
+
+        for (i, waypoint) in self.waypoints.iter_mut().enumerate() {
+            waypoint.connections.retain(|connection| {
+                let j = connection.waypoint_index;
+                !removed.contains(&(i.min(j), i.max(j)))
+            });
+        }
+
+        removed.len()
+    }
+
+    /// Chooses a spread-out subset of waypoints in which no two are directly connected, and
+    /// to which no other waypoint can be added without breaking that rule, e.g. for placing
+    /// sensors evenly across the graph. Connections are treated as undirected.
+    ///
+    /// Waypoints are chosen greedily, visiting those with the fewest connections first, as
+    /// choosing a well connected waypoint rules out all of its neighbors. This tends to give
+    /// a large set, but not necessarily the largest possible one.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the chosen waypoints, in ascending order. Waypoints
+    ///   without any connections are always included.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// for lon in [0.0, 1.0, 3.0, 6.0, 10.0] {
+    ///     dataset.add_new_waypoint(0.0, lon).unwrap();
+    /// }
+    /// dataset.assign_all_connections_naive(1);
+    /// dataset.normalize_connections();
+    ///
+    /// // The waypoints form a path 0 - 1 - 2 - 3 - 4
+    /// let chosen = dataset.maximal_independent_set();
+    /// assert_eq!(chosen, vec![0, 2, 4]);
+    /// ```
+    pub fn maximal_independent_set(&self) -> Vec<usize> {
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); self.waypoints.len()];
+        for (from, to, _) in self.undirected_edges() {
+            neighbors[from].push(to);
+            neighbors[to].push(from);
+        }
+
+        let mut order: Vec<usize> = (0..self.waypoints.len()).collect();
+        order.sort_by_key(|&index| (neighbors[index].len(), index));
+
+        let mut blocked = vec![false; self.waypoints.len()];
+        let mut chosen: Vec<usize> = Vec::new();
+        for index in order {
+            if !blocked[index] {
+                chosen.push(index);
+                for &neighbor in &neighbors[index] {
+                    blocked[neighbor] = true;
+                }
+            }
+        }
+
+        chosen.sort_unstable();
+        chosen
+    }
+
+    /// Estimates how central each waypoint is to routing through the graph, e.g. to find
+    /// bottlenecks and hubs. Routes are found between `samples` randomly chosen pairs of
+    /// waypoints with `get_shortest_route_by_index`, and each waypoint is scored by the
+    /// fraction of those routes passing through it (not counting the routes starting or
+    /// ending at it). This approximates betweenness centrality for graphs too large to route
+    /// between every pair of waypoints; more samples give a closer approximation.
+    ///
+    /// # Parameters
+    ///
+    /// - `samples`: The number of random pairs of waypoints to route between. Pairs with no
+    ///   route between them still count towards the total.
+    /// - `seed`: The seed for choosing the pairs, so that results are repeatable. Any seed may
+    ///   be used, including `0`.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<f32>`: The score of each waypoint, between `0.0` and `1.0`, in the same order as
+    ///   `waypoints`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// for lon in [0.0, 1.0, 3.0] {
+    ///     dataset.add_new_waypoint(0.0, lon).unwrap();
+    /// }
+    /// dataset.assign_all_connections_naive(1);
+    /// dataset.normalize_connections();
+    ///
+    /// // Every route between the two ends passes through the middle
+    /// let scores = dataset.sampled_betweenness(100, 7);
+    /// assert!(scores[1] > 0.0);
+    /// assert_eq!((scores[0], scores[2]), (0.0, 0.0));
+    ///
+    /// // A seed of 0 still chooses varied pairs
+    /// assert!(dataset.sampled_betweenness(100, 0)[1] > 0.0);
+    /// ```
+    pub fn sampled_betweenness(&self, samples: usize, seed: u64) -> Vec<f32> {
+        if self.waypoints.is_empty() || samples == 0 {
+            return vec![0.0; self.waypoints.len()];
+        }
+
+        let mut counts = vec![0usize; self.waypoints.len()];
+        let mut rng = pseudo_random::XorShiftRng::new(seed);
+        for _ in 0..samples {
+            let start = rng.next_u32() as usize % self.waypoints.len();
+            let goal = rng.next_u32() as usize % self.waypoints.len();
+
+            if let Some(route) = self.get_shortest_route_by_index(start, goal) {
+                for &index in route.iter().skip(1).take(route.len().saturating_sub(2)) {
+                    counts[index] += 1;
+                }
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|count| count as f32 / samples as f32)
+            .collect()
+    }
+
+    /// Collects the dataset's connections as undirected edges. All of the connections between
+    /// a pair of waypoints, in either direction, form a single edge with the shortest of
+    /// their distances.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(usize, usize, f32)>`: The edges as `(from, to, distance)`, with `from < to`,
+    ///   sorted by distance and then by index.
+    fn undirected_edges(&self) -> Vec<(usize, usize, f32)> {
+        let mut edges: Vec<(usize, usize, f32)> = Vec::new();
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                let j = connection.waypoint_index;
+                if i != j {
+                    edges.push((i.min(j), i.max(j), connection.distance));
+                }
+            }
+        }
+        // Bring the connections between each pair together, shortest first, and keep only that
+        edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)).then(a.2.total_cmp(&b.2)));
+        edges.dedup_by_key(|edge| (edge.0, edge.1));
+
+        edges.sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+        edges
+    }
+
+    /// Estimates the time taken to travel a route, from the distance and speed of each
+    /// connection along it.
+    ///
+    /// # Arguments
+    ///
+    /// - `route`: The indices of the waypoints along the route, in order.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(f32)`: The travel time in hours, which is `0.0` for a route of fewer than two
+    ///   waypoints.
+    /// - `None`: If consecutive waypoints along the route aren't connected, or if any
+    ///   connection travelled has no speed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index in `route` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let b = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let c = dataset.add_new_waypoint(0.0, 2.0).unwrap();
+    /// dataset.assign_all_connections_naive(2);
+    ///
+    /// assert_eq!(dataset.get_route_eta(&[a, b, c]), None);
+    ///
+    /// for waypoint in &mut dataset.waypoints {
+    ///     for connection in &mut waypoint.connections {
+    ///         connection.speed_kph = Some(100.0);
+    ///     }
+    /// }
+    ///
+    /// // Two degrees of longitude at the equator is about 222km
+    /// let hours = dataset.get_route_eta(&[a, b, c]).unwrap();
+    /// assert!((hours - 2.22).abs() < 0.01);
+    /// ```
+    pub fn get_route_eta(&self, route: &[usize]) -> Option<f32> {
+        route
+            .windows(2)
+            .map(|pair| {
+                let connection = self.waypoints[pair[0]]
+                    .connections
+                    .iter()
+                    .find(|connection| connection.waypoint_index == pair[1])?;
+
+                connection
+                    .speed_kph
+                    .map(|speed| connection.distance / speed)
+            })
+            .sum()
+    }
+
+    /// Prints details of a route between waypoints, including the waypoints' labels,
+    /// coordinates, and total route distance.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - An optional vector of waypoint indices representing the route.
     ///
     /// # Example
     ///
     /// ```
     /// let mut dataset = zpath::Dataset::new();
     /// dataset.generate_waypoints(100);
-    /// dataset.assign_all_connections_geohash(3)
+    /// dataset.assign_all_connections_geohash(3);
     ///
     /// let waypoint_a = &dataset.waypoints[0];
     /// let waypoint_b = &dataset.waypoints[1];
     ///
-    /// let route = dataset.get_shortest_route(waypoint_a, waypoint_b);
+    /// let route = dataset.get_shortest_route(waypoint_a, waypoint_b).unwrap();
     ///
     /// dataset.print_route_details(route);
     /// ```
@@ -748,7 +7015,7 @@ impl Dataset {
                     if i < route.len() - 1 {
                         print!(", ");
                         let next_waypoint = &self.waypoints[route[i + 1]];
-                        let distance = current_waypoint.get_distance_to(next_waypoint);
+                        let distance = self.distance_km(current_waypoint, next_waypoint);
                         route_distance += distance;
                         println!("travel {:.2}km to", distance)
                     }
@@ -761,4 +7028,404 @@ impl Dataset {
             }
         }
     }
+
+    /// Determines whether every connection in the dataset has a matching connection in
+    /// the opposite direction.
+    ///
+    /// # Returns
+    ///
+    /// - `bool`: `true` if all connections are mutual, otherwise `false`.
+    fn connections_are_symmetric(&self) -> bool {
+        let mut edges = HashSet::new();
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                edges.insert((i, connection.waypoint_index));
+            }
+        }
+
+        edges.iter().all(|&(from, to)| edges.contains(&(to, from)))
+    }
+
+    /// Renders the dataset's connections as a graph in the Graphviz DOT language, with each
+    /// waypoint as a node labeled by its `label` and each connection as an edge labeled by
+    /// its distance in kilometers. If every connection is mutual, an undirected `graph` is
+    /// produced with a single edge per pair; otherwise a `digraph` is produced. Quotes and
+    /// backslashes in labels are escaped, so any label produces valid DOT.
+    ///
+    /// # Returns
+    ///
+    /// - `String`: The DOT source, which can be rendered with e.g. `dot -Tpng`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let dot = dataset.to_dot();
+    /// assert!(dot.starts_with("graph {"));
+    /// assert!(dot.contains("0 -- 1 [label=\"111.3\"];"));
+    ///
+    /// dataset.waypoints[0].label = String::from(r#"a"b\c"#);
+    /// assert!(dataset.to_dot().contains(r#"0 [label="a\"b\\c"];"#));
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let symmetric = self.connections_are_symmetric();
+        let (graph_type, edge_op) = if symmetric {
+            ("graph", "--")
+        } else {
+            ("digraph", "->")
+        };
+
+        let mut dot = format!("{} {{\n", graph_type);
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            let label = waypoint.label.replace('\\', "\\\\").replace('"', "\\\"");
+            dot.push_str(&format!("    {} [label=\"{}\"];\n", i, label));
+        }
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                // Mutual connections are only drawn once, from the lower index
+                if symmetric && connection.waypoint_index < i {
+                    continue;
+                }
+
+                dot.push_str(&format!(
+                    "    {} {} {} [label=\"{:.1}\"];\n",
+                    i, edge_op, connection.waypoint_index, connection.distance
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Writes the dataset's connections as a compact binary edge list, for loading the graph
+    /// into other tools without parsing text. Every value is little-endian: a `u32` count of
+    /// waypoints, followed by a `(u32 from, u32 to, f32 distance)` triple for each connection
+    /// until the end of the output. Connections are written in the order they're held, so
+    /// mutual connections appear once in each direction.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: The destination of the edge list, e.g. a file.
+    ///
+    /// # Returns
+    ///
+    /// - `io::Result<()>`: Any error from the writer, or `io::ErrorKind::InvalidData` if the
+    ///   dataset has more waypoints than fit in 32-bit indices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// dataset.assign_all_connections_naive(1);
+    ///
+    /// let mut edges = Vec::new();
+    /// dataset.write_edge_list(&mut edges).unwrap();
+    ///
+    /// // A 4 byte header, then 12 bytes for each of the 2 connections
+    /// assert_eq!(edges.len(), 4 + 2 * 12);
+    /// assert_eq!(edges[..4], 2u32.to_le_bytes());
+    /// assert_eq!(edges[4..8], 0u32.to_le_bytes());
+    /// assert_eq!(edges[8..12], 1u32.to_le_bytes());
+    /// let distance = f32::from_le_bytes(edges[12..16].try_into().unwrap());
+    /// assert_eq!(distance, dataset.waypoints[0].connections[0].distance);
+    /// ```
+    pub fn write_edge_list(&self, writer: impl io::Write) -> io::Result<()> {
+        let to_u32 = |index: usize| {
+            u32::try_from(index).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "waypoint index exceeds 32 bits")
+            })
+        };
+
+        let mut writer = io::BufWriter::new(writer);
+        writer.write_all(&to_u32(self.waypoints.len())?.to_le_bytes())?;
+
+        for (i, waypoint) in self.waypoints.iter().enumerate() {
+            for connection in &waypoint.connections {
+                writer.write_all(&to_u32(i)?.to_le_bytes())?;
+                writer.write_all(&to_u32(connection.waypoint_index)?.to_le_bytes())?;
+                writer.write_all(&connection.distance.to_le_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Converts a route into a polyline ready for display, e.g. for turn-by-turn navigation:
+    /// the coordinates of each waypoint along the route, together with the initial bearing
+    /// from it to the next waypoint (see `Waypoint::get_bearing_to`). The last waypoint has
+    /// no next waypoint, so it repeats the bearing of the waypoint before it.
+    ///
+    /// # Arguments
+    ///
+    /// - `route`: The indices of the waypoints along the route, in order.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<(Coord, Coord, f32)>`: The `(lat, lon, bearing)` of each waypoint along the
+    ///   route, with bearings in degrees clockwise from North. A route of a single waypoint
+    ///   has a bearing of `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index in `route` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let east = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let north = dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    ///
+    /// let polyline = dataset.route_polyline(&[start, east, north]);
+    /// let bearings: Vec<f32> = polyline.iter().map(|&(_, _, bearing)| bearing.round()).collect();
+    /// assert_eq!(bearings, vec![90.0, 0.0, 0.0]);
+    /// assert_eq!((polyline[1].0, polyline[1].1), (0.0, 1.0));
+    /// ```
+    pub fn route_polyline(&self, route: &[usize]) -> Vec<(Coord, Coord, f32)> {
+        let mut polyline: Vec<(Coord, Coord, f32)> = Vec::with_capacity(route.len());
+
+        for (position, &index) in route.iter().enumerate() {
+            let waypoint = &self.waypoints[index];
+            let bearing = match route.get(position + 1) {
+                Some(&next) => waypoint.get_bearing_to(&self.waypoints[next]),
+                None => polyline.last().map_or(0.0, |&(_, _, bearing)| bearing),
+            };
+
+            polyline.push((waypoint.lat, waypoint.lon, bearing));
+        }
+
+        polyline
+    }
+
+    /// Builds a profile of a route for display, e.g. for a navigation UI announcing
+    /// "sharp left in 200m": the distance covered to reach each waypoint along the route and
+    /// the turn made there. The turn is the difference between the heading the route arrives
+    /// at the waypoint on and the heading it leaves on (see `Waypoint::get_bearing_to`). A
+    /// great-circle segment's heading changes along its length, so the arriving heading is
+    /// taken at the waypoint itself, as the reverse of the bearing back along the segment.
+    ///
+    /// # Arguments
+    ///
+    /// - `route`: The indices of the waypoints along the route, in order.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<RouteStep>`: A step for each waypoint along the route. The first step has a
+    ///   segment distance of `0.0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index in `route` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let east = dataset.add_new_waypoint(0.0, 1.0).unwrap();
+    /// let north = dataset.add_new_waypoint(1.0, 1.0).unwrap();
+    ///
+    /// let profile = dataset.route_profile(&[start, east, north]);
+    /// let turns: Vec<f32> = profile.iter().map(|step| step.heading_change.round()).collect();
+    /// assert_eq!(turns, vec![0.0, -90.0, 0.0]);
+    ///
+    /// let total = dataset.waypoints[start].get_distance_to(&dataset.waypoints[east])
+    ///     + dataset.waypoints[east].get_distance_to(&dataset.waypoints[north]);
+    /// assert_eq!(profile[2].cumulative_distance, total);
+    ///
+    /// // Waypoints along a single great circle make no turns, though its heading changes
+    /// let (lat, lon) = dataset.waypoints[start].destination(45.0, 3000.0);
+    /// let middle = dataset.add_new_waypoint(lat, lon).unwrap();
+    /// let (lat, lon) = dataset.waypoints[start].destination(45.0, 6000.0);
+    /// let end = dataset.add_new_waypoint(lat, lon).unwrap();
+    /// let profile = dataset.route_profile(&[start, middle, end]);
+    /// assert!(profile[1].heading_change.abs() < 0.1);
+    /// ```
+    pub fn route_profile(&self, route: &[usize]) -> Vec<RouteStep> {
+        let mut profile = Vec::with_capacity(route.len());
+        let mut cumulative_distance = 0.0;
+
+        for (position, &index) in route.iter().enumerate() {
+            let waypoint = &self.waypoints[index];
+            let previous = position.checked_sub(1).map(|p| &self.waypoints[route[p]]);
+            let next = route.get(position + 1).map(|&n| &self.waypoints[n]);
+
+            let segment_distance =
+                previous.map_or(0.0, |previous| self.distance_km(previous, waypoint));
+            cumulative_distance += segment_distance;
+
+            let heading_change = match (previous, next) {
+                (Some(previous), Some(next)) => {
+                    let incoming = waypoint.get_bearing_to(previous) + 180.0;
+                    let turn = waypoint.get_bearing_to(next) - incoming;
+                    (turn + 540.0).rem_euclid(360.0) - 180.0
+                }
+                _ => 0.0,
+            };
+
+            profile.push(RouteStep {
+                waypoint_index: index,
+                cumulative_distance,
+                segment_distance,
+                heading_change,
+            });
+        }
+
+        profile
+    }
+
+    /// Simplifies a route for display using the Ramer-Douglas-Peucker algorithm, dropping
+    /// waypoints that deviate from the simplified line by less than a given tolerance. The
+    /// deviation of a waypoint is its distance from the great-circle segment between the
+    /// waypoints kept either side of it, and the route's endpoints are always kept.
+    ///
+    /// # Arguments
+    ///
+    /// - `route`: The indices of the waypoints along the route, in order.
+    /// - `tolerance_km`: The largest deviation in kilometers that may be dropped.
+    ///
+    /// # Returns
+    ///
+    /// - `Vec<usize>`: The indices of the waypoints kept, in route order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index in `route` is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let start = dataset.add_new_waypoint(0.0, 0.0).unwrap();
+    /// let detour = dataset.add_new_waypoint(1.0, 2.0).unwrap();
+    /// let wobble = dataset.add_new_waypoint(0.55, 3.0).unwrap();
+    /// let end = dataset.add_new_waypoint(0.0, 4.0).unwrap();
+    ///
+    /// // The wobble lies a few kilometers off the line from the detour to the end
+    /// let route = vec![start, detour, wobble, end];
+    /// assert_eq!(dataset.simplify_route(&route, 10.0), vec![start, detour, end]);
+    /// assert_eq!(dataset.simplify_route(&route, 200.0), vec![start, end]);
+    /// ```
+    pub fn simplify_route(&self, route: &[usize], tolerance_km: f32) -> Vec<usize> {
+        if route.len() <= 2 {
+            return route.to_vec();
+        }
+
+        let mut keep = vec![false; route.len()];
+        keep[0] = true;
+        keep[route.len() - 1] = true;
+
+        // Deviations are measured on a sphere of `EARTH_RADIUS`; scale to the dataset's radius
+        let scale = self.earth_radius / EARTH_RADIUS;
+
+        // Each range is split at its farthest waypoint until no waypoint is out of tolerance
+        let mut ranges = vec![(0, route.len() - 1)];
+        while let Some((first, last)) = ranges.pop() {
+            let a = &self.waypoints[route[first]];
+            let b = &self.waypoints[route[last]];
+
+            let farthest = (first + 1..last)
+                .map(|i| {
+                    (
+                        i,
+                        scale * self.waypoints[route[i]].distance_to_segment(a, b),
+                    )
+                })
+                .max_by(|x, y| x.1.total_cmp(&y.1));
+
+            if let Some((i, deviation)) = farthest {
+                if deviation > tolerance_km {
+                    keep[i] = true;
+                    ranges.push((first, i));
+                    ranges.push((i, last));
+                }
+            }
+        }
+
+        route
+            .iter()
+            .zip(keep)
+            .filter(|(_, kept)| *kept)
+            .map(|(&index, _)| index)
+            .collect()
+    }
+
+    /// Renders a route as a `LINESTRING` in the Well-Known Text (WKT) format, for loading
+    /// into spatial databases such as PostGIS. As in `Waypoint::to_wkt`, each coordinate is
+    /// written as longitude then latitude.
+    ///
+    /// # Arguments
+    ///
+    /// - `route`: The indices of the waypoints along the route, in order.
+    ///
+    /// # Returns
+    ///
+    /// - `String`: The WKT representation of the route. An empty route produces
+    ///   `LINESTRING EMPTY`, and a route of a single waypoint produces a zero-length line
+    ///   that starts and ends at that waypoint, since a line needs at least two points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut dataset = zpath::Dataset::new();
+    /// let a = dataset.add_new_waypoint(37.5, -122.25).unwrap();
+    /// let b = dataset.add_new_waypoint(34.0, -118.5).unwrap();
+    ///
+    /// assert_eq!(dataset.route_to_wkt(&[a, b]), "LINESTRING(-122.25 37.5, -118.5 34)");
+    /// assert_eq!(dataset.route_to_wkt(&[]), "LINESTRING EMPTY");
+    /// ```
+    pub fn route_to_wkt(&self, route: &[usize]) -> String {
+        if route.is_empty() {
+            return String::from("LINESTRING EMPTY");
+        }
+
+        let mut points: Vec<String> = route
+            .iter()
+            .map(|&index| {
+                format!(
+                    "{} {}",
+                    self.waypoints[index].lon, self.waypoints[index].lat
+                )
+            })
+            .collect();
+        if points.len() == 1 {
+            points.push(points[0].clone());
+        }
+
+        format!("LINESTRING({})", points.join(", "))
+    }
+}
+
+impl SpatialIndex for Dataset {
+    fn nearest(&self, lat: Coord, lon: Coord, k: usize) -> Vec<usize> {
+        let probe = Waypoint::new(lat, lon, String::new());
+
+        self.get_knn_geohash(&probe, k, None)
+            .into_iter()
+            .map(|connection| connection.waypoint_index)
+            .collect()
+    }
+
+    fn within(&self, lat: Coord, lon: Coord, radius_km: f32) -> Vec<usize> {
+        let probe = Waypoint::new(lat, lon, String::new());
+
+        let mut found: Vec<usize> = self
+            .get_within_radius(&probe, radius_km, None)
+            .into_iter()
+            .map(|connection| connection.waypoint_index)
+            .collect();
+        found.sort_unstable();
+        found
+    }
 }
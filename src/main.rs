@@ -1,4 +1,4 @@
-fn main() {
+fn main() -> Result<(), zpath::ZpathError> {
     // Create a new, empty dataset
     let mut dataset = zpath::Dataset::new();
 
@@ -14,14 +14,16 @@ fn main() {
 
     // Add custom waypoints to the dataset whenever you'd like; the indexes are
     // returned if you'd like to use them in searches
-    dataset.add_new_waypoint(39.9658, -86.0207);
-    let custom_waypoint_index = dataset.add_new_waypoint(37.7749, -122.4194);
+    dataset.add_new_waypoint(39.9658, -86.0207)?;
+    let custom_waypoint_index = dataset.add_new_waypoint(37.7749, -122.4194)?;
     let custom_waypoint = &dataset.waypoints[custom_waypoint_index];
 
     // Find the shortest route between two waypoints
     let waypoint_a = &dataset.waypoints[0];
-    let route = dataset.get_shortest_route(waypoint_a, custom_waypoint);
+    let route = dataset.get_shortest_route(waypoint_a, custom_waypoint)?;
 
     // Print details about the route (if one was discovered)
     dataset.print_route_details(route);
+
+    Ok(())
 }
@@ -6,7 +6,9 @@ pub struct XorShiftRng {
 }
 
 impl XorShiftRng {
-    /// Creates a new instance of XorShiftRng with the given seed value.
+    /// Creates a new instance of XorShiftRng with the given seed value. XorShift would only
+    /// ever produce zeroes from a seed of 0, so that seed is replaced with a fixed non-zero
+    /// constant instead.
     ///
     /// # Arguments
     ///
@@ -16,7 +18,15 @@ impl XorShiftRng {
     ///
     /// A new XorShiftRng instance.
     pub fn new(seed: u64) -> Self {
-        XorShiftRng { seed }
+        const ZERO_SEED_REPLACEMENT: u64 = 0x9E37_79B9_7F4A_7C15;
+
+        XorShiftRng {
+            seed: if seed == 0 {
+                ZERO_SEED_REPLACEMENT
+            } else {
+                seed
+            },
+        }
     }
 
     /// Generates the next random 32-bit unsigned integer using the XorShift algorithm.
@@ -43,8 +53,7 @@ impl XorShiftRng {
     /// A random 32-bit floating-point number within the specified range.
     pub fn random_f32_in_range(&mut self, min: f32, max: f32) -> f32 {
         let random_f64 = self.next_u32() as f64 / (u32::MAX as f64);
-        let scaled_f32 = min + (max - min) * random_f64 as f32;
-        scaled_f32
+        min + (max - min) * random_f64 as f32
     }
 }
 /// LcgRng is a pseudorandom number generator based on the Linear Congruential Generator (LCG) algorithm.
@@ -99,7 +108,6 @@ impl LcgRng {
     /// A random 32-bit floating-point number within the specified range.
     pub fn random_f32_in_range(&mut self, min: f32, max: f32) -> f32 {
         let random_f64 = self.next_f64();
-        let scaled_f32 = min + (max - min) * random_f64 as f32;
-        scaled_f32
+        min + (max - min) * random_f64 as f32
     }
 }